@@ -3,78 +3,172 @@
 //! This module uses clap's builder API instead of derive macros
 //! to enable runtime i18n for help text.
 
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
 use clap::{Arg, ArgAction, ArgMatches, Command};
+use rsendmail_core::relay::Relay;
 use rsendmail_core::Config;
-use rsendmail_i18n::{tr, Language};
+use rsendmail_i18n::{tr, tr_with_args, Language};
+use std::path::Path;
+use std::str::FromStr;
 
 /// Build the CLI command with localized help text
+///
+/// Running without a subcommand is the same as `send`, so existing command lines keep working.
+/// The other subcommands only take the options they use: `test` checks the connection and
+/// authentication, `validate` lints the EML files and `estimate` prints the preflight summary.
 pub fn build_cli() -> Command {
-    Command::new("rsendmail")
+    send_args(Command::new("rsendmail"))
         .version(env!("CARGO_PKG_VERSION"))
         .author("RSendMail Contributors")
         .about(tr("cli.about"))
-        // Required arguments
-        .arg(
-            Arg::new("smtp_server")
-                .long("smtp-server")
-                .help(tr("cli.smtp_server"))
-                .required(true),
+        .args_conflicts_with_subcommands(true)
+        .subcommand_negates_reqs(true)
+        .subcommand(send_args(Command::new("send")).about(tr("cli.send_about")))
+        .subcommand(
+            Command::new("test")
+                .about(tr("cli.test_about"))
+                .args(common_args())
+                .args(connection_args())
+                .mut_arg("smtp_server", |arg| arg.required_unless_present("config")),
+        )
+        .subcommand(
+            Command::new("validate")
+                .about(tr("cli.validate_about"))
+                .args(common_args())
+                .args(source_args())
+                .mut_arg("dir", |arg| arg.required_unless_present_any(["eml_file", "config"])),
         )
+        .subcommand(
+            Command::new("estimate")
+                .about(tr("cli.estimate_about"))
+                .args(common_args())
+                .args(source_args())
+                .args(recipient_args())
+                .args(pacing_args())
+                .mut_arg("dir", |arg| arg.required_unless_present_any(["eml_file", "config"])),
+        )
+}
+
+/// All options of `send`, which is also the top-level command
+fn send_args(command: Command) -> Command {
+    command
+        .args(common_args())
+        .args(connection_args())
+        .mut_arg("smtp_server", |arg| {
+            arg.required_unless_present_any([
+                "config",
+                "direct_mx",
+                "validate_only",
+                "count_only",
+                "print_message",
+            ])
+        })
+        // Required arguments (unless provided by --config)
         .arg(
             Arg::new("from")
                 .long("from")
                 .help(tr("cli.from"))
-                .required_unless_present("dir"),
+                .required_unless_present_any(["dir", "eml_file", "config"]),
+        )
+        .arg(
+            Arg::new("reply_to")
+                .long("reply-to")
+                .help(tr("cli.reply_to")),
         )
         .arg(
-            Arg::new("to")
-                .long("to")
-                .help(tr("cli.to"))
-                .required_unless_present("dir"),
+            Arg::new("sender_header")
+                .long("sender-header")
+                .help(tr("cli.sender_header")),
         )
+        .args(recipient_args())
+        .mut_arg("to", |arg| {
+            arg.required_unless_present_any(["dir", "eml_file", "jsonl_input", "config"])
+        })
+        .args(source_args())
+        .mut_arg("dir", |arg| {
+            arg.required_unless_present_any([
+                "attachment",
+                "attachments",
+                "attachment_dir",
+                "eml_file",
+                "jsonl_input",
+                "config",
+            ])
+            .conflicts_with_all(["attachment", "attachments", "attachment_dir"])
+        })
+        .mut_arg("eml_file", |arg| {
+            arg.conflicts_with_all(["attachment", "attachments", "attachment_dir"])
+        })
         // Optional arguments with defaults
+        .args(pacing_args())
+        .arg(
+            Arg::new("dedup")
+                .long("dedup")
+                .help(tr("cli.dedup"))
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("dedup_header")
+                .long("dedup-header")
+                .help(tr("cli.dedup_header"))
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("strict_parse")
+                .long("strict-parse")
+                .help(tr("cli.strict_parse"))
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("fail_fast")
+                .long("fail-fast")
+                .help(tr("cli.fail_fast"))
+                .action(ArgAction::SetTrue),
+        )
         .arg(
-            Arg::new("port")
-                .long("port")
-                .help(tr("cli.port"))
-                .default_value("25"),
+            Arg::new("max_connections")
+                .long("max-connections")
+                .help(tr("cli.max_connections")),
         )
         .arg(
-            Arg::new("dir")
-                .long("dir")
-                .help(tr("cli.dir"))
-                .required_unless_present_any(["attachment", "attachment_dir"])
-                .conflicts_with_all(["attachment", "attachment_dir"]),
+            Arg::new("per_host_connections")
+                .long("per-host-connections")
+                .help(tr("cli.per_host_connections")),
         )
         .arg(
-            Arg::new("extension")
-                .long("extension")
-                .help(tr("cli.extension"))
-                .default_value("eml"),
+            Arg::new("batch_max_bytes")
+                .long("batch-max-bytes")
+                .help(tr("cli.batch_max_bytes")),
         )
         .arg(
-            Arg::new("processes")
-                .long("processes")
-                .help(tr("cli.processes"))
-                .default_value("auto"),
+            Arg::new("adaptive_batch")
+                .long("adaptive-batch")
+                .help(tr("cli.adaptive_batch"))
+                .action(ArgAction::SetTrue),
         )
         .arg(
-            Arg::new("batch_size")
-                .long("batch-size")
-                .help(tr("cli.batch_size"))
+            Arg::new("adaptive_batch_min")
+                .long("adaptive-batch-min")
+                .help(tr("cli.adaptive_batch_min"))
                 .default_value("1"),
         )
         .arg(
-            Arg::new("smtp_timeout")
-                .long("smtp-timeout")
-                .help(tr("cli.smtp_timeout"))
-                .default_value("30"),
+            Arg::new("adaptive_batch_max")
+                .long("adaptive-batch-max")
+                .help(tr("cli.adaptive_batch_max"))
+                .default_value("100"),
+        )
+        .arg(
+            Arg::new("max_run_seconds")
+                .long("max-run-seconds")
+                .help(tr("cli.max_run_seconds")),
         )
         .arg(
-            Arg::new("log_level")
-                .long("log-level")
-                .help(tr("cli.log_level"))
-                .default_value("info"),
+            Arg::new("progress")
+                .long("progress")
+                .help(tr("cli.progress"))
+                .action(ArgAction::SetTrue),
         )
         // Boolean flags
         .arg(
@@ -83,30 +177,109 @@ pub fn build_cli() -> Command {
                 .help(tr("cli.envelope_cc_bcc"))
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("separate_envelopes")
+                .long("separate-envelopes")
+                .help(tr("cli.separate_envelopes"))
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("group_by_domain")
+                .long("group-by-domain")
+                .help(tr("cli.group_by_domain"))
+                .action(ArgAction::SetTrue)
+                .conflicts_with("separate_envelopes"),
+        )
+        .arg(
+            Arg::new("verp")
+                .long("verp")
+                .help(tr("cli.verp"))
+                .action(ArgAction::SetTrue)
+                .requires("separate_envelopes"),
+        )
+        .arg(
+            Arg::new("verp_base")
+                .long("verp-base")
+                .help(tr("cli.verp_base")),
+        )
+        .arg(
+            Arg::new("recipient_from_sidecar")
+                .long("recipient-from-sidecar")
+                .help(tr("cli.recipient_from_sidecar"))
+                .action(ArgAction::SetTrue),
+        )
         .arg(
             Arg::new("keep_headers")
                 .long("keep-headers")
                 .help(tr("cli.keep_headers"))
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("envelope_from_header")
+                .long("envelope-from-header")
+                .help(tr("cli.envelope_from_header"))
+                .action(ArgAction::SetTrue),
+        )
         .arg(
             Arg::new("anonymize_emails")
                 .long("anonymize-emails")
                 .help(tr("cli.anonymize_emails"))
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("anonymize_phones")
+                .long("anonymize-phones")
+                .help(tr("cli.anonymize_phones"))
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("anonymize_ips")
+                .long("anonymize-ips")
+                .help(tr("cli.anonymize_ips"))
+                .action(ArgAction::SetTrue),
+        )
         .arg(
             Arg::new("anonymize_domain")
                 .long("anonymize-domain")
                 .help(tr("cli.anonymize_domain"))
                 .default_value("example.com"),
         )
+        .arg(
+            Arg::new("anonymize_key")
+                .long("anonymize-key")
+                .help(tr("cli.anonymize_key"))
+                .env("RSENDMAIL_ANONYMIZE_KEY")
+                .hide_env_values(true),
+        )
         .arg(
             Arg::new("modify_headers")
                 .long("modify-headers")
                 .help(tr("cli.modify_headers"))
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("regenerate_message_id")
+                .long("regenerate-message-id")
+                .help(tr("cli.regenerate_message_id"))
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("no_preserve_date")
+                .long("no-preserve-date")
+                .help(tr("cli.no_preserve_date"))
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("generate_text_fallback")
+                .long("generate-text-fallback")
+                .help(tr("cli.generate_text_fallback"))
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("output_charset")
+                .long("output-charset")
+                .help(tr("cli.output_charset")),
+        )
         .arg(
             Arg::new("loop")
                 .long("loop")
@@ -125,23 +298,87 @@ pub fn build_cli() -> Command {
                 .help(tr("cli.loop_interval"))
                 .default_value("1"),
         )
+        .arg(
+            Arg::new("send_window")
+                .long("send-window")
+                .help(tr("cli.send_window")),
+        )
         .arg(
             Arg::new("retry_interval")
                 .long("retry-interval")
                 .help(tr("cli.retry_interval"))
                 .default_value("5"),
         )
+        .arg(
+            Arg::new("retry_backoff")
+                .long("retry-backoff")
+                .help(tr("cli.retry_backoff"))
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("retry_max_interval")
+                .long("retry-max-interval")
+                .help(tr("cli.retry_max_interval"))
+                .default_value("300"),
+        )
+        .arg(
+            Arg::new("message_retries")
+                .long("message-retries")
+                .help(tr("cli.message_retries"))
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("message_retry_delay_ms")
+                .long("message-retry-delay-ms")
+                .help(tr("cli.message_retry_delay_ms"))
+                .default_value("1000"),
+        )
+        .arg(
+            Arg::new("retry_round")
+                .long("retry-round")
+                .help(tr("cli.retry_round"))
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("abort_error_rate")
+                .long("abort-on-error-rate")
+                .help(tr("cli.abort_error_rate")),
+        )
+        .arg(
+            Arg::new("abort_min_samples")
+                .long("abort-min-samples")
+                .help(tr("cli.abort_min_samples"))
+                .default_value("100"),
+        )
         // Attachment options
         .arg(
             Arg::new("attachment")
                 .long("attachment")
                 .help(tr("cli.attachment")),
         )
+        .arg(
+            Arg::new("attachments")
+                .long("attach")
+                .help(tr("cli.attach"))
+                .action(ArgAction::Append)
+                .conflicts_with("attachment_dir"),
+        )
         .arg(
             Arg::new("attachment_dir")
                 .long("attachment-dir")
                 .help(tr("cli.attachment_dir")),
         )
+        .arg(
+            Arg::new("jsonl_input")
+                .long("jsonl-input")
+                .help(tr("cli.jsonl_input"))
+                .conflicts_with_all(["dir", "eml_file", "attachment", "attachments", "attachment_dir"]),
+        )
+        .arg(
+            Arg::new("max_attachment_size")
+                .long("max-attachment-size")
+                .help(tr("cli.max_attachment_size")),
+        )
         // Template options
         .arg(
             Arg::new("subject_template")
@@ -159,39 +396,51 @@ pub fn build_cli() -> Command {
                 .help(tr("cli.html_template")),
         )
         .arg(
-            Arg::new("email_send_interval_ms")
-                .long("email-send-interval-ms")
-                .help(tr("cli.email_send_interval_ms"))
+            Arg::new("inline_images")
+                .long("inline-image")
+                .help(tr("cli.inline_image"))
+                .action(ArgAction::Append)
+                .requires("html_template"),
+        )
+        .arg(
+            Arg::new("interval_jitter_ms")
+                .long("interval-jitter-ms")
+                .help(tr("cli.interval_jitter_ms"))
                 .default_value("0"),
         )
-        // Authentication options
         .arg(
-            Arg::new("auth_mode")
-                .long("auth-mode")
-                .help(tr("cli.auth_mode"))
-                .action(ArgAction::SetTrue),
+            Arg::new("warmup_seconds")
+                .long("warmup-seconds")
+                .help(tr("cli.warmup_seconds"))
+                .default_value("0")
+                .requires("max_rate_per_second"),
         )
         .arg(
-            Arg::new("username")
-                .long("username")
-                .help(tr("cli.username")),
+            Arg::new("warmup_start_rate")
+                .long("warmup-start-rate")
+                .help(tr("cli.warmup_start_rate"))
+                .default_value("1")
+                .requires("warmup_seconds"),
         )
+        // Relay options
         .arg(
-            Arg::new("password")
-                .long("password")
-                .help(tr("cli.password")),
+            Arg::new("relays")
+                .long("relays")
+                .help(tr("cli.relays"))
+                .action(ArgAction::Append)
+                .value_delimiter(',')
+                .value_parser(parse_relay),
         )
-        // TLS options
         .arg(
-            Arg::new("use_tls")
-                .long("use-tls")
-                .help(tr("cli.use_tls"))
-                .action(ArgAction::SetTrue),
+            Arg::new("relay_cooldown_secs")
+                .long("relay-cooldown")
+                .help(tr("cli.relay_cooldown"))
+                .default_value("60"),
         )
         .arg(
-            Arg::new("accept_invalid_certs")
-                .long("accept-invalid-certs")
-                .help(tr("cli.accept_invalid_certs"))
+            Arg::new("direct_mx")
+                .long("direct-mx")
+                .help(tr("cli.direct_mx"))
                 .action(ArgAction::SetTrue),
         )
         // Logging options
@@ -201,19 +450,381 @@ pub fn build_cli() -> Command {
                 .help(tr("cli.failed_emails_dir")),
         )
         .arg(
-            Arg::new("log_file")
-                .long("log-file")
-                .help(tr("cli.log_file")),
+            Arg::new("move_hard_bounces")
+                .long("move-hard-bounces")
+                .help(tr("cli.move_hard_bounces"))
+                .action(ArgAction::SetTrue)
+                .requires("failed_emails_dir"),
+        )
+        .arg(
+            Arg::new("sent_emails_dir")
+                .long("sent-emails-dir")
+                .help(tr("cli.sent_emails_dir")),
+        )
+        .arg(
+            Arg::new("move_on_success")
+                .long("move-on-success")
+                .help(tr("cli.move_on_success"))
+                .action(ArgAction::SetTrue)
+                .requires("sent_emails_dir"),
+        )
+        .arg(
+            Arg::new("stats_json")
+                .long("stats-json")
+                .help(tr("cli.stats_json")),
+        )
+        .arg(
+            Arg::new("failed_csv")
+                .long("failed-csv")
+                .help(tr("cli.failed_csv")),
+        )
+        .arg(
+            Arg::new("checkpoint_file")
+                .long("checkpoint-file")
+                .help(tr("cli.checkpoint_file")),
+        )
+        .arg(
+            Arg::new("reset_checkpoint")
+                .long("reset-checkpoint")
+                .help(tr("cli.reset_checkpoint"))
+                .action(ArgAction::SetTrue)
+                .requires("checkpoint_file"),
+        )
+        .arg(
+            Arg::new("manifest_file")
+                .long("manifest-file")
+                .help(tr("cli.manifest_file")),
+        )
+        .arg(
+            Arg::new("on_success_command")
+                .long("on-success-command")
+                .help(tr("cli.on_success_command")),
+        )
+        .arg(
+            Arg::new("on_failure_command")
+                .long("on-failure-command")
+                .help(tr("cli.on_failure_command")),
+        )
+        .arg(
+            Arg::new("report_to")
+                .long("send-report-email")
+                .help(tr("cli.send_report_email")),
+        )
+        .arg(
+            Arg::new("header")
+                .long("header")
+                .help(tr("cli.header"))
+                .action(ArgAction::Append)
+                .value_parser(parse_header),
+        )
+        .arg(
+            Arg::new("pipelining")
+                .long("pipelining")
+                .help(tr("cli.pipelining"))
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("use_bdat")
+                .long("use-bdat")
+                .help(tr("cli.use_bdat"))
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("bdat_chunk_size")
+                .long("bdat-chunk-size")
+                .help(tr("cli.bdat_chunk_size"))
+                .default_value("1048576"),
+        )
+        .arg(
+            Arg::new("dkim_private_key")
+                .long("dkim-key")
+                .help(tr("cli.dkim_private_key"))
+                .requires_all(["dkim_selector", "dkim_domain"]),
         )
-        // Language option (parsed early, before other args)
         .arg(
-            Arg::new("lang")
-                .long("lang")
-                .help(tr("cli.lang"))
-                .env("RSENDMAIL_LANG"),
+            Arg::new("dkim_selector")
+                .long("dkim-selector")
+                .help(tr("cli.dkim_selector"))
+                .requires("dkim_private_key"),
+        )
+        .arg(
+            Arg::new("dkim_domain")
+                .long("dkim-domain")
+                .help(tr("cli.dkim_domain"))
+                .requires("dkim_private_key"),
+        )
+        .arg(
+            Arg::new("shuffle")
+                .long("shuffle")
+                .help(tr("cli.shuffle"))
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("shuffle_seed")
+                .long("shuffle-seed")
+                .help(tr("cli.shuffle_seed"))
+                .requires("shuffle"),
+        )
+        .arg(
+            Arg::new("limit")
+                .long("limit")
+                .help(tr("cli.limit")),
+        )
+        .arg(
+            Arg::new("allow_empty")
+                .long("allow-empty")
+                .help(tr("cli.allow_empty"))
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("dry_run")
+                .long("dry-run")
+                .help(tr("cli.dry_run"))
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("validate_only")
+                .long("validate-only")
+                .help(tr("cli.validate_only"))
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("count_only")
+                .long("count-only")
+                .help(tr("cli.count_only"))
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("print_message")
+                .long("print-message")
+                .help(tr("cli.print_message")),
+        )
+        .arg(
+            Arg::new("print_message_output")
+                .long("print-message-output")
+                .help(tr("cli.print_message_output"))
+                .requires("print_message"),
+        )
+        .arg(
+            Arg::new("confirm")
+                .long("confirm")
+                .help(tr("cli.confirm"))
+                .action(ArgAction::SetTrue),
         )
 }
 
+/// Options shared by every subcommand: config file, logging and language
+fn common_args() -> Vec<Arg> {
+    vec![
+        Arg::new("config")
+            .long("config")
+            .help(tr("cli.config")),
+        Arg::new("log_level")
+            .long("log-level")
+            .help(tr("cli.log_level"))
+            .default_value("info"),
+        Arg::new("log_format")
+            .long("log-format")
+            .help(tr("cli.log_format"))
+            .value_parser(["text", "json"])
+            .default_value("text"),
+        Arg::new("quiet")
+            .long("quiet")
+            .help(tr("cli.quiet"))
+            .action(ArgAction::SetTrue),
+        Arg::new("color")
+            .long("color")
+            .help(tr("cli.color"))
+            .value_parser(["auto", "always", "never"]),
+        Arg::new("no_color")
+            .long("no-color")
+            .help(tr("cli.no_color"))
+            .action(ArgAction::SetTrue)
+            .conflicts_with("color"),
+        Arg::new("log_file")
+            .long("log-file")
+            .help(tr("cli.log_file")),
+        // Language option (parsed early, before other args)
+        Arg::new("lang")
+            .long("lang")
+            .help(tr("cli.lang"))
+            .env("RSENDMAIL_LANG"),
+    ]
+}
+
+/// SMTP server, authentication and TLS options
+fn connection_args() -> Vec<Arg> {
+    vec![
+        Arg::new("smtp_server")
+            .long("smtp-server")
+            .help(tr("cli.smtp_server")),
+        Arg::new("port")
+            .long("port")
+            .help(tr("cli.port"))
+            .default_value("25"),
+        Arg::new("protocol")
+            .long("protocol")
+            .help(tr("cli.protocol"))
+            .value_parser(["smtp", "lmtp"])
+            .default_value("smtp"),
+        Arg::new("ehlo_name")
+            .long("ehlo-name")
+            .help(tr("cli.ehlo_name")),
+        Arg::new("smtp_timeout")
+            .long("smtp-timeout")
+            .help(tr("cli.smtp_timeout"))
+            .default_value("30"),
+        Arg::new("connect_timeout")
+            .long("connect-timeout")
+            .help(tr("cli.connect_timeout")),
+        Arg::new("data_timeout")
+            .long("data-timeout")
+            .help(tr("cli.data_timeout")),
+        Arg::new("auth_mode")
+            .long("auth-mode")
+            .help(tr("cli.auth_mode"))
+            .action(ArgAction::SetTrue),
+        Arg::new("username")
+            .long("username")
+            .help(tr("cli.username")),
+        Arg::new("password")
+            .long("password")
+            .help(tr("cli.password")),
+        Arg::new("password_file")
+            .long("password-file")
+            .help(tr("cli.password_file")),
+        Arg::new("oauth2_token")
+            .long("oauth2-token")
+            .help(tr("cli.oauth2_token")),
+        Arg::new("auth_mechanism")
+            .long("auth-mechanism")
+            .help(tr("cli.auth_mechanism"))
+            .value_parser(["plain", "login", "cram-md5", "xoauth2"]),
+        Arg::new("use_tls")
+            .long("use-tls")
+            .help(tr("cli.use_tls"))
+            .action(ArgAction::SetTrue),
+        Arg::new("require_starttls")
+            .long("require-starttls")
+            .help(tr("cli.require_starttls"))
+            .action(ArgAction::SetTrue),
+        Arg::new("accept_invalid_certs")
+            .long("accept-invalid-certs")
+            .help(tr("cli.accept_invalid_certs"))
+            .action(ArgAction::SetTrue),
+        Arg::new("pinned_cert_sha256")
+            .long("pinned-cert-sha256")
+            .help(tr("cli.pinned_cert_sha256")),
+        Arg::new("tls_fallback")
+            .long("tls-fallback")
+            .help(tr("cli.tls_fallback"))
+            .action(ArgAction::SetTrue),
+        Arg::new("proxy")
+            .long("proxy")
+            .help(tr("cli.proxy")),
+    ]
+}
+
+/// Where the EML files come from and which of them are picked up
+fn source_args() -> Vec<Arg> {
+    vec![
+        Arg::new("dir")
+            .long("dir")
+            .help(tr("cli.dir")),
+        Arg::new("eml_file")
+            .long("eml-file")
+            .help(tr("cli.eml_file"))
+            .conflicts_with("dir"),
+        Arg::new("extension")
+            .long("extension")
+            .help(tr("cli.extension"))
+            .default_value("eml"),
+        Arg::new("decompress")
+            .long("decompress")
+            .help(tr("cli.decompress"))
+            .action(ArgAction::SetTrue),
+        Arg::new("include_glob")
+            .long("include-glob")
+            .help(tr("cli.include_glob"))
+            .requires("dir"),
+        Arg::new("exclude_glob")
+            .long("exclude-glob")
+            .help(tr("cli.exclude_glob"))
+            .requires("dir"),
+        Arg::new("modified_after")
+            .long("modified-after")
+            .visible_alias("since")
+            .help(tr("cli.modified_after"))
+            .requires("dir"),
+    ]
+}
+
+/// Recipients given on the command line
+fn recipient_args() -> Vec<Arg> {
+    vec![
+        Arg::new("to")
+            .long("to")
+            .help(tr("cli.to")),
+        Arg::new("bcc")
+            .long("bcc")
+            .help(tr("cli.bcc")),
+        Arg::new("recipient_allow_domains")
+            .long("recipient-allow-domain")
+            .help(tr("cli.recipient_allow_domain"))
+            .action(ArgAction::Append)
+            .value_delimiter(','),
+        Arg::new("recipient_block_domains")
+            .long("recipient-block-domain")
+            .help(tr("cli.recipient_block_domain"))
+            .action(ArgAction::Append)
+            .value_delimiter(','),
+    ]
+}
+
+/// Concurrency and pacing options that determine how long a run takes
+fn pacing_args() -> Vec<Arg> {
+    vec![
+        Arg::new("processes")
+            .long("processes")
+            .help(tr("cli.processes"))
+            .default_value("auto"),
+        Arg::new("batch_size")
+            .long("batch-size")
+            .help(tr("cli.batch_size"))
+            .default_value("1"),
+        Arg::new("email_send_interval_ms")
+            .long("email-send-interval-ms")
+            .help(tr("cli.email_send_interval_ms"))
+            .default_value("0"),
+        Arg::new("max_rate_per_second")
+            .long("max-rate-per-second")
+            .help(tr("cli.max_rate_per_second"))
+            .conflicts_with("email_send_interval_ms"),
+    ]
+}
+
+/// Parse a `--header "Name: Value"` argument
+fn parse_header(spec: &str) -> Result<(String, String), String> {
+    let invalid = || tr_with_args("cli_main.invalid_header", &[("header", spec)]);
+    let (name, value) = spec.split_once(':').ok_or_else(invalid)?;
+    let name = name.trim();
+    let value = value.trim();
+    if name.is_empty()
+        || !name.bytes().all(|b| b.is_ascii_graphic())
+        || value.contains(['\r', '\n'])
+    {
+        return Err(invalid());
+    }
+    Ok((name.to_string(), value.to_string()))
+}
+
+/// Validate a `--relays` entry (`host[:port][*weight]`)
+fn parse_relay(spec: &str) -> Result<String, String> {
+    Relay::parse(spec, 25)
+        .map(|_| spec.trim().to_string())
+        .ok_or_else(|| tr_with_args("cli_main.invalid_relay", &[("relay", spec)]))
+}
+
 /// Detect language from command line args or environment
 /// This is called before full CLI parsing to set the language
 pub fn detect_language() -> Language {
@@ -244,77 +855,512 @@ pub fn detect_language() -> Language {
     Language::from_system()
 }
 
-/// Parse CLI arguments and return Config
-pub fn parse_args() -> Config {
+/// What the command line asked rsendmail to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Send,
+    Test,
+    Validate,
+    Estimate,
+}
+
+/// Parse CLI arguments and return the selected subcommand with its Config
+pub fn parse_args() -> (Action, Config) {
     let matches = build_cli().get_matches();
-    matches_to_config(&matches)
+    let (action, matches) = subcommand(&matches);
+    (action, matches_to_config(matches, action))
+}
+
+/// The selected subcommand and its matches; without one the top-level matches are `send`
+fn subcommand(matches: &ArgMatches) -> (Action, &ArgMatches) {
+    match matches.subcommand() {
+        Some(("test", sub)) => (Action::Test, sub),
+        Some(("validate", sub)) => (Action::Validate, sub),
+        Some(("estimate", sub)) => (Action::Estimate, sub),
+        Some((_, sub)) => (Action::Send, sub),
+        None => (Action::Send, matches),
+    }
 }
 
 /// Convert ArgMatches to Config
-fn matches_to_config(matches: &ArgMatches) -> Config {
-    Config {
-        smtp_server: matches.get_one::<String>("smtp_server").unwrap().clone(),
-        port: matches
-            .get_one::<String>("port")
-            .unwrap()
-            .parse()
-            .unwrap_or(25),
-        from: matches.get_one::<String>("from").cloned(),
-        to: matches.get_one::<String>("to").cloned(),
-        envelope_cc_bcc: matches.get_flag("envelope_cc_bcc"),
-        dir: matches.get_one::<String>("dir").cloned(),
-        extension: matches.get_one::<String>("extension").unwrap().clone(),
-        processes: matches.get_one::<String>("processes").unwrap().clone(),
-        batch_size: matches
-            .get_one::<String>("batch_size")
-            .unwrap()
-            .parse()
-            .unwrap_or(1),
-        smtp_timeout: matches
-            .get_one::<String>("smtp_timeout")
-            .unwrap()
-            .parse()
-            .unwrap_or(30),
-        log_level: matches.get_one::<String>("log_level").unwrap().clone(),
-        keep_headers: matches.get_flag("keep_headers"),
-        anonymize_emails: matches.get_flag("anonymize_emails"),
-        anonymize_domain: matches
-            .get_one::<String>("anonymize_domain")
-            .unwrap()
-            .clone(),
-        modify_headers: matches.get_flag("modify_headers"),
-        r#loop: matches.get_flag("loop"),
-        repeat: matches
-            .get_one::<String>("repeat")
-            .unwrap()
-            .parse()
-            .unwrap_or(1),
-        loop_interval: matches
-            .get_one::<String>("loop_interval")
-            .unwrap()
-            .parse()
-            .unwrap_or(1),
-        retry_interval: matches
-            .get_one::<String>("retry_interval")
-            .unwrap()
-            .parse()
-            .unwrap_or(5),
-        attachment: matches.get_one::<String>("attachment").cloned(),
-        attachment_dir: matches.get_one::<String>("attachment_dir").cloned(),
-        subject_template: matches.get_one::<String>("subject_template").cloned(),
-        text_template: matches.get_one::<String>("text_template").cloned(),
-        html_template: matches.get_one::<String>("html_template").cloned(),
-        email_send_interval_ms: matches
-            .get_one::<String>("email_send_interval_ms")
-            .unwrap()
-            .parse()
-            .unwrap_or(0),
-        auth_mode: matches.get_flag("auth_mode"),
-        username: matches.get_one::<String>("username").cloned(),
-        password: matches.get_one::<String>("password").cloned(),
-        use_tls: matches.get_flag("use_tls"),
-        accept_invalid_certs: matches.get_flag("accept_invalid_certs"),
-        failed_emails_dir: matches.get_one::<String>("failed_emails_dir").cloned(),
-        log_file: matches.get_one::<String>("log_file").cloned(),
+///
+/// With `--config`, the file is loaded first and only options given on the command
+/// line override its values (defaults < config file < CLI flags); without it, clap's
+/// defaults apply as usual. The password is resolved here (--password, then
+/// --password-file, then RSENDMAIL_SMTP_PASSWORD) so an unreadable password file is
+/// reported as a usage error. Which settings must end up present depends on `action`.
+fn matches_to_config(matches: &ArgMatches, action: Action) -> Config {
+    let config_path = matches.get_one::<String>("config");
+    let base = match config_path {
+        Some(path) => Config::from_file(Path::new(path)).unwrap_or_else(|e| {
+            build_cli()
+                .error(
+                    ErrorKind::Io,
+                    tr_with_args(
+                        "cli_main.config_load_failed",
+                        &[("path", path), ("error", &e.to_string())],
+                    ),
+                )
+                .exit()
+        }),
+        None => Config::default(),
+    };
+    let overrides = Overrides {
+        matches,
+        has_config_file: config_path.is_some(),
+    };
+    let mut config = overrides.apply(base);
+
+    let no_source = config.dir.is_none()
+        && config.eml_file.is_none()
+        && config.attachment_files().is_empty()
+        && config.attachment_dir.is_none();
+    let required = match action {
+        Action::Send => vec![
+            (
+                "smtp-server",
+                config.smtp_server.is_empty()
+                    && !config.direct_mx
+                    && !config.validate_only
+                    && !config.count_only
+                    && config.print_message.is_none(),
+            ),
+            ("dir", no_source && config.jsonl_input.is_none()),
+            ("from", config.dir.is_none() && config.eml_file.is_none() && config.from.is_none()),
+            (
+                "to",
+                config.dir.is_none()
+                    && config.eml_file.is_none()
+                    && config.jsonl_input.is_none()
+                    && config.to.is_none(),
+            ),
+        ],
+        Action::Test => vec![("smtp-server", config.smtp_server.is_empty())],
+        Action::Validate | Action::Estimate => vec![("dir", no_source)],
+    };
+    for (arg, missing) in required {
+        if missing {
+            build_cli()
+                .error(
+                    ErrorKind::MissingRequiredArgument,
+                    tr_with_args("cli_main.config_missing_arg", &[("arg", arg)]),
+                )
+                .exit();
+        }
+    }
+
+    match config.resolve_password() {
+        Ok(password) => config.password = password,
+        Err(e) => build_cli()
+            .error(
+                ErrorKind::Io,
+                tr_with_args(
+                    "core.mailer.password_file_read_failed",
+                    &[
+                        ("path", config.password_file.as_deref().unwrap_or_default()),
+                        ("error", &e.to_string()),
+                    ],
+                ),
+            )
+            .exit(),
+    }
+    config
+}
+
+/// Applies command-line values onto a base `Config`
+struct Overrides<'a> {
+    matches: &'a ArgMatches,
+    has_config_file: bool,
+}
+
+impl Overrides<'_> {
+    /// Whether `id` should override the base value: always when given on the command
+    /// line (or via env), and for clap defaults only when no config file was loaded
+    fn provided(&self, id: &str) -> bool {
+        match self.source(id) {
+            Some(ValueSource::DefaultValue) => !self.has_config_file,
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    /// Where the value of `id` came from; None also for options the subcommand does not take
+    fn source(&self, id: &str) -> Option<ValueSource> {
+        match self.matches.try_contains_id(id) {
+            Ok(_) => self.matches.value_source(id),
+            Err(_) => None,
+        }
+    }
+
+    fn string(&self, id: &str, target: &mut String) {
+        if self.provided(id) {
+            if let Some(value) = self.matches.get_one::<String>(id) {
+                *target = value.clone();
+            }
+        }
+    }
+
+    fn opt_string(&self, id: &str, target: &mut Option<String>) {
+        if self.provided(id) {
+            *target = self.matches.get_one::<String>(id).cloned();
+        }
+    }
+
+    fn flag(&self, id: &str, target: &mut bool) {
+        if self.provided(id) {
+            *target = self.matches.get_flag(id);
+        }
+    }
+
+    /// Parses the value, falling back to `fallback` when it is not a valid `T`
+    fn parsed<T: FromStr>(&self, id: &str, target: &mut T, fallback: T) {
+        if self.provided(id) {
+            if let Some(value) = self.matches.get_one::<String>(id) {
+                *target = value.parse().unwrap_or(fallback);
+            }
+        }
+    }
+
+    fn opt_parsed<T: FromStr>(&self, id: &str, target: &mut Option<T>) {
+        if self.provided(id) {
+            *target = self
+                .matches
+                .get_one::<String>(id)
+                .and_then(|value| value.parse().ok());
+        }
+    }
+
+    fn apply(&self, mut config: Config) -> Config {
+        // --dir, --eml-file, --attachment/--attach, --attachment-dir and --jsonl-input select the
+        // sending mode; choosing one on the command line replaces whichever mode the config file selected
+        if ["dir", "eml_file", "attachment", "attachments", "attachment_dir", "jsonl_input"]
+            .iter()
+            .any(|id| self.source(id) == Some(ValueSource::CommandLine))
+        {
+            config.dir = None;
+            config.eml_file = None;
+            config.attachment = None;
+            config.attachments.clear();
+            config.attachment_dir = None;
+            config.jsonl_input = None;
+        }
+
+        self.string("smtp_server", &mut config.smtp_server);
+        self.parsed("port", &mut config.port, 25);
+        self.opt_string("ehlo_name", &mut config.ehlo_name);
+        self.string("protocol", &mut config.protocol);
+        self.opt_string("from", &mut config.from);
+        self.opt_string("to", &mut config.to);
+        self.opt_string("bcc", &mut config.bcc);
+        self.opt_string("reply_to", &mut config.reply_to);
+        self.opt_string("sender_header", &mut config.sender_header);
+        for (id, domains) in [
+            ("recipient_allow_domains", &mut config.recipient_allow_domains),
+            ("recipient_block_domains", &mut config.recipient_block_domains),
+        ] {
+            if self.provided(id) {
+                *domains = self
+                    .matches
+                    .get_many::<String>(id)
+                    .map(|values| values.cloned().collect());
+            }
+        }
+        self.flag("envelope_cc_bcc", &mut config.envelope_cc_bcc);
+        self.flag("separate_envelopes", &mut config.separate_envelopes);
+        self.flag("group_by_domain", &mut config.group_by_domain);
+        self.flag("verp", &mut config.verp);
+        self.opt_string("verp_base", &mut config.verp_base);
+        self.flag("recipient_from_sidecar", &mut config.recipient_from_sidecar);
+        self.opt_string("dir", &mut config.dir);
+        self.opt_string("eml_file", &mut config.eml_file);
+        self.opt_string("jsonl_input", &mut config.jsonl_input);
+        self.string("extension", &mut config.extension);
+        self.flag("decompress", &mut config.decompress);
+        self.flag("dedup", &mut config.dedup);
+        self.flag("dedup_header", &mut config.dedup_header);
+        self.flag("strict_parse", &mut config.strict_parse);
+        self.flag("fail_fast", &mut config.fail_fast);
+        self.opt_string("include_glob", &mut config.include_glob);
+        self.opt_string("exclude_glob", &mut config.exclude_glob);
+        self.opt_string("modified_after", &mut config.modified_after);
+        self.string("processes", &mut config.processes);
+        self.opt_parsed("max_connections", &mut config.max_connections);
+        self.opt_parsed("per_host_connections", &mut config.per_host_connections);
+        self.parsed("batch_size", &mut config.batch_size, 1);
+        self.opt_parsed("batch_max_bytes", &mut config.batch_max_bytes);
+        self.flag("adaptive_batch", &mut config.adaptive_batch);
+        self.parsed("adaptive_batch_min", &mut config.adaptive_batch_min, 1);
+        self.parsed("adaptive_batch_max", &mut config.adaptive_batch_max, 100);
+        self.parsed("smtp_timeout", &mut config.smtp_timeout, 30);
+        self.opt_parsed("connect_timeout", &mut config.connect_timeout);
+        self.opt_parsed("data_timeout", &mut config.data_timeout);
+        self.opt_parsed("max_run_seconds", &mut config.max_run_seconds);
+        self.string("log_level", &mut config.log_level);
+        self.string("log_format", &mut config.log_format);
+        self.flag("quiet", &mut config.quiet);
+        self.opt_string("color", &mut config.color);
+        if self.provided("no_color") && self.matches.get_flag("no_color") {
+            config.color = Some("never".to_string());
+        }
+        self.flag("progress", &mut config.progress);
+        self.flag("keep_headers", &mut config.keep_headers);
+        self.flag("envelope_from_header", &mut config.envelope_from_header);
+        self.flag("anonymize_emails", &mut config.anonymize_emails);
+        self.flag("anonymize_phones", &mut config.anonymize_phones);
+        self.flag("anonymize_ips", &mut config.anonymize_ips);
+        self.string("anonymize_domain", &mut config.anonymize_domain);
+        self.opt_string("anonymize_key", &mut config.anonymize_key);
+        self.flag("modify_headers", &mut config.modify_headers);
+        self.flag("regenerate_message_id", &mut config.regenerate_message_id);
+        if self.provided("no_preserve_date") && self.matches.get_flag("no_preserve_date") {
+            config.preserve_date = false;
+        }
+        self.flag("generate_text_fallback", &mut config.generate_text_fallback);
+        self.opt_string("output_charset", &mut config.output_charset);
+        self.flag("loop", &mut config.r#loop);
+        self.parsed("repeat", &mut config.repeat, 1);
+        self.parsed("loop_interval", &mut config.loop_interval, 1);
+        self.opt_string("send_window", &mut config.send_window);
+        self.parsed("retry_interval", &mut config.retry_interval, 5);
+        self.flag("retry_backoff", &mut config.retry_backoff);
+        self.parsed("retry_max_interval", &mut config.retry_max_interval, 300);
+        self.parsed("message_retries", &mut config.message_retries, 0);
+        self.parsed("message_retry_delay_ms", &mut config.message_retry_delay_ms, 1000);
+        self.flag("retry_round", &mut config.retry_round);
+        self.opt_parsed("abort_error_rate", &mut config.abort_error_rate);
+        self.parsed("abort_min_samples", &mut config.abort_min_samples, 100);
+        self.opt_string("attachment", &mut config.attachment);
+        if self.provided("attachments") {
+            config.attachments = self
+                .matches
+                .get_many::<String>("attachments")
+                .map(|values| values.cloned().collect())
+                .unwrap_or_default();
+        }
+        self.opt_string("attachment_dir", &mut config.attachment_dir);
+        self.opt_parsed("max_attachment_size", &mut config.max_attachment_bytes);
+        self.opt_string("subject_template", &mut config.subject_template);
+        self.opt_string("text_template", &mut config.text_template);
+        self.opt_string("html_template", &mut config.html_template);
+        if self.provided("inline_images") {
+            config.inline_images = self
+                .matches
+                .get_many::<String>("inline_images")
+                .map(|values| values.cloned().collect())
+                .unwrap_or_default();
+        }
+        self.parsed("email_send_interval_ms", &mut config.email_send_interval_ms, 0);
+        self.parsed("interval_jitter_ms", &mut config.interval_jitter_ms, 0);
+        self.opt_parsed("max_rate_per_second", &mut config.max_rate_per_second);
+        self.parsed("warmup_seconds", &mut config.warmup_seconds, 0);
+        self.parsed("warmup_start_rate", &mut config.warmup_start_rate, 1.0);
+        self.flag("auth_mode", &mut config.auth_mode);
+        self.opt_string("username", &mut config.username);
+        self.opt_string("password", &mut config.password);
+        self.opt_string("password_file", &mut config.password_file);
+        self.opt_string("oauth2_token", &mut config.oauth2_token);
+        self.opt_string("auth_mechanism", &mut config.auth_mechanism);
+        self.flag("use_tls", &mut config.use_tls);
+        self.flag("require_starttls", &mut config.require_starttls);
+        self.flag("accept_invalid_certs", &mut config.accept_invalid_certs);
+        self.opt_string("pinned_cert_sha256", &mut config.pinned_cert_sha256);
+        self.flag("tls_fallback", &mut config.tls_fallback);
+        self.opt_string("proxy", &mut config.proxy);
+        if self.provided("relays") {
+            config.relays = self
+                .matches
+                .get_many::<String>("relays")
+                .map(|values| values.cloned().collect())
+                .unwrap_or_default();
+        }
+        self.parsed("relay_cooldown_secs", &mut config.relay_cooldown_secs, 60);
+        self.flag("direct_mx", &mut config.direct_mx);
+        self.opt_string("failed_emails_dir", &mut config.failed_emails_dir);
+        self.flag("move_hard_bounces", &mut config.move_hard_bounces);
+        self.opt_string("sent_emails_dir", &mut config.sent_emails_dir);
+        self.flag("move_on_success", &mut config.move_on_success);
+        self.opt_string("log_file", &mut config.log_file);
+        self.opt_string("stats_json", &mut config.stats_json);
+        self.opt_string("failed_csv", &mut config.failed_csv);
+        self.opt_string("report_to", &mut config.report_to);
+        self.opt_string("checkpoint_file", &mut config.checkpoint_file);
+        self.flag("reset_checkpoint", &mut config.reset_checkpoint);
+        self.opt_string("manifest_file", &mut config.manifest_file);
+        self.opt_string("on_success_command", &mut config.on_success_command);
+        self.opt_string("on_failure_command", &mut config.on_failure_command);
+        self.flag("pipelining", &mut config.pipelining);
+        self.flag("use_bdat", &mut config.use_bdat);
+        self.parsed("bdat_chunk_size", &mut config.bdat_chunk_size, 1024 * 1024);
+        self.opt_string("dkim_private_key", &mut config.dkim_private_key);
+        self.opt_string("dkim_selector", &mut config.dkim_selector);
+        self.opt_string("dkim_domain", &mut config.dkim_domain);
+        self.flag("shuffle", &mut config.shuffle);
+        self.opt_parsed("shuffle_seed", &mut config.shuffle_seed);
+        self.opt_parsed("limit", &mut config.limit);
+        self.flag("allow_empty", &mut config.allow_empty);
+        self.flag("dry_run", &mut config.dry_run);
+        self.flag("validate_only", &mut config.validate_only);
+        self.flag("count_only", &mut config.count_only);
+        self.opt_string("print_message", &mut config.print_message);
+        self.opt_string("print_message_output", &mut config.print_message_output);
+        self.flag("confirm", &mut config.confirm);
+        if self.provided("header") {
+            config.extra_headers = self
+                .matches
+                .get_many::<(String, String)>("header")
+                .map(|headers| headers.cloned().collect())
+                .unwrap_or_default();
+        }
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn config_from(args: &[&str]) -> Config {
+        let (action, config) = parse_from(args);
+        assert_eq!(action, Action::Send);
+        config
+    }
+
+    fn parse_from(args: &[&str]) -> (Action, Config) {
+        let matches = build_cli()
+            .try_get_matches_from(std::iter::once("rsendmail").chain(args.iter().copied()))
+            .unwrap();
+        let (action, matches) = subcommand(&matches);
+        (action, matches_to_config(matches, action))
+    }
+
+    fn parse_error(args: &[&str]) -> ErrorKind {
+        build_cli()
+            .try_get_matches_from(std::iter::once("rsendmail").chain(args.iter().copied()))
+            .unwrap_err()
+            .kind()
+    }
+
+    #[test]
+    fn test_config_file_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(
+            &path,
+            r#"{"smtp_server": "mx.example.com", "port": 2525, "dir": "/var/mail",
+                "processes": "4", "keep_headers": true}"#,
+        )
+        .unwrap();
+
+        let config = config_from(&["--config", path.to_str().unwrap()]);
+        assert_eq!(config.smtp_server, "mx.example.com");
+        assert_eq!(config.port, 2525);
+        assert_eq!(config.dir.as_deref(), Some("/var/mail"));
+        assert_eq!(config.processes, "4");
+        assert!(config.keep_headers);
+        // Not in the file: Config defaults rather than clap defaults
+        assert_eq!(config.smtp_timeout, Config::default().smtp_timeout);
+    }
+
+    #[test]
+    fn test_cli_flags_override_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(
+            &path,
+            "smtp_server = \"mx.example.com\"\nport = 2525\ndir = \"/var/mail\"\nbatch_size = 10\n",
+        )
+        .unwrap();
+
+        let config = config_from(&[
+            "--config",
+            path.to_str().unwrap(),
+            "--port",
+            "587",
+            "--attachment",
+            "report.pdf",
+            "--from",
+            "a@example.com",
+            "--to",
+            "b@example.com",
+        ]);
+        assert_eq!(config.smtp_server, "mx.example.com");
+        assert_eq!(config.port, 587);
+        assert_eq!(config.batch_size, 10);
+        // Choosing a mode on the command line replaces the file's mode
+        assert_eq!(config.dir, None);
+        assert_eq!(config.attachment.as_deref(), Some("report.pdf"));
+    }
+
+    #[test]
+    fn test_repeated_header_flags() {
+        let config = config_from(&[
+            "--smtp-server",
+            "mx.example.com",
+            "--dir",
+            "/var/mail",
+            "--header",
+            "X-Campaign-Id: spring",
+            "--header",
+            "X-RSendMail-Run:{filename}",
+        ]);
+        assert_eq!(
+            config.extra_headers,
+            vec![
+                ("X-Campaign-Id".to_string(), "spring".to_string()),
+                ("X-RSendMail-Run".to_string(), "{filename}".to_string()),
+            ]
+        );
+        assert!(parse_header("no colon").is_err());
+        assert!(parse_header("Bad Name: value").is_err());
+    }
+
+    #[test]
+    fn test_without_config_file_uses_clap_defaults() {
+        let config = config_from(&["--smtp-server", "mx.example.com", "--dir", "/var/mail"]);
+        assert_eq!(config.port, 25);
+        assert_eq!(config.extension, "eml");
+        assert_eq!(config.processes, "auto");
+    }
+
+    #[test]
+    fn test_each_subcommand_parses_its_minimal_args() {
+        let (action, config) =
+            parse_from(&["send", "--smtp-server", "mx.example.com", "--dir", "/var/mail"]);
+        assert_eq!(action, Action::Send);
+        assert_eq!(config.smtp_server, "mx.example.com");
+        assert_eq!(config.dir.as_deref(), Some("/var/mail"));
+
+        let (action, config) =
+            parse_from(&["test", "--smtp-server", "mx.example.com", "--auth-mode"]);
+        assert_eq!(action, Action::Test);
+        assert_eq!(config.smtp_server, "mx.example.com");
+        assert!(config.auth_mode);
+        assert_eq!(config.dir, None);
+
+        let (action, config) = parse_from(&["validate", "--dir", "/var/mail"]);
+        assert_eq!(action, Action::Validate);
+        assert_eq!(config.dir.as_deref(), Some("/var/mail"));
+        assert!(config.smtp_server.is_empty());
+
+        let (action, config) =
+            parse_from(&["estimate", "--dir", "/var/mail", "--processes", "4"]);
+        assert_eq!(action, Action::Estimate);
+        assert_eq!(config.processes, "4");
+    }
+
+    #[test]
+    fn test_subcommands_reject_missing_and_foreign_args() {
+        assert_eq!(parse_error(&["test"]), ErrorKind::MissingRequiredArgument);
+        assert_eq!(parse_error(&["validate"]), ErrorKind::MissingRequiredArgument);
+        assert_eq!(
+            parse_error(&["estimate", "--smtp-server", "mx.example.com"]),
+            ErrorKind::UnknownArgument
+        );
+        assert_eq!(
+            parse_error(&["validate", "--dir", "/var/mail", "--from", "a@example.com"]),
+            ErrorKind::UnknownArgument
+        );
+        assert_eq!(parse_error(&["send", "--dir", "/var/mail"]), ErrorKind::MissingRequiredArgument);
     }
 }
@@ -1,34 +1,194 @@
-use log::LevelFilter;
+use chrono::Local;
+use indicatif::ProgressBar;
+use log::{LevelFilter, Log, Metadata, Record};
+use serde_json::json;
 use simplelog::*;
 use std::fs::File;
+use std::io::Write;
+use std::sync::Mutex;
+
+/// 每条日志输出一行JSON对象的日志记录器，供日志采集系统使用
+struct JsonLogger {
+    level: LevelFilter,
+    file: Option<Mutex<File>>,
+}
+
+impl Log for JsonLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format_json(record);
+        let _ = writeln!(std::io::stdout().lock(), "{}", line);
+        if let Some(file) = &self.file {
+            let _ = writeln!(file.lock().unwrap(), "{}", line);
+        }
+    }
+
+    fn flush(&self) {
+        let _ = std::io::stdout().flush();
+        if let Some(file) = &self.file {
+            let _ = file.lock().unwrap().flush();
+        }
+    }
+}
+
+/// 将日志记录序列化为单行JSON：level、timestamp、target、message，
+/// 源文件位置可用时附带 file 与 line 字段；消息中的换行和引号按JSON规则转义
+fn format_json(record: &Record) -> String {
+    let mut value = json!({
+        "timestamp": Local::now().to_rfc3339(),
+        "level": record.level().as_str(),
+        "target": record.target(),
+        "message": record.args().to_string(),
+    });
+    if let Some(file) = record.file() {
+        value["file"] = json!(file);
+    }
+    if let Some(line) = record.line() {
+        value["line"] = json!(line);
+    }
+    value.to_string()
+}
+
+/// 显示进度条时的控制台输出：按整行写到标准错误，写入期间暂时清除进度条，
+/// 避免日志与进度条互相覆盖
+struct BarWriter {
+    bar: ProgressBar,
+    buffer: Vec<u8>,
+}
+
+impl Write for BarWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if let Some(end) = self.buffer.iter().rposition(|byte| *byte == b'\n') {
+            let lines: Vec<u8> = self.buffer.drain(..=end).collect();
+            self.bar.suspend(|| std::io::stderr().write_all(&lines))?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        if !self.buffer.is_empty() {
+            let rest = std::mem::take(&mut self.buffer);
+            self.bar.suspend(|| std::io::stderr().write_all(&rest))?;
+        }
+        std::io::stderr().flush()
+    }
+}
+
+/// 将 `color` 设置（auto/always/never）映射为终端颜色选项
+///
+/// 未设置或为 auto 时，若设置了非空的 `NO_COLOR` 环境变量则不着色，否则仅在输出到终端时着色；
+/// 显式的 always/never 优先于 `NO_COLOR`
+fn color_choice(color: Option<&str>, no_color_env: bool) -> ColorChoice {
+    match color.map(str::to_ascii_lowercase).as_deref() {
+        Some("always") => ColorChoice::Always,
+        Some("never") => ColorChoice::Never,
+        _ if no_color_env => ColorChoice::Never,
+        _ => ColorChoice::Auto,
+    }
+}
+
+/// `log_format` 为 `json` 时每条日志输出一行JSON，否则使用可读的文本格式；
+/// 传入 `bar` 时控制台日志不着色，统一写到标准错误并显示在进度条上方
+pub fn init_logging(
+    level: LevelFilter,
+    log_file: Option<&str>,
+    log_format: &str,
+    color: Option<&str>,
+    bar: Option<ProgressBar>,
+) {
+    if log_format.eq_ignore_ascii_case("json") {
+        let file = log_file.map(|log_file_path| {
+            Mutex::new(
+                File::create(log_file_path)
+                    .unwrap_or_else(|e| panic!("无法创建日志文件 {}: {}", log_file_path, e)),
+            )
+        });
+        log::set_boxed_logger(Box::new(JsonLogger { level, file }))
+            .unwrap_or_else(|e| panic!("初始化日志失败: {}", e));
+        log::set_max_level(level);
+        return;
+    }
+
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    let color = color_choice(color, no_color_env);
 
-pub fn init_logging(level: LevelFilter, log_file: Option<&str>) {
     // 配置日志格式
     let mut config_builder = ConfigBuilder::new();
     config_builder.set_time_format_rfc3339();
     let _ = config_builder.set_time_offset_to_local();
     let log_config = config_builder.build();
 
+    let console: Box<dyn SharedLogger> = match bar {
+        Some(bar) => WriteLogger::new(
+            level,
+            log_config.clone(),
+            BarWriter {
+                bar,
+                buffer: Vec::new(),
+            },
+        ),
+        None => TermLogger::new(level, log_config.clone(), TerminalMode::Mixed, color),
+    };
+
     if let Some(log_file_path) = log_file {
         // 如果指定了日志文件，同时输出到控制台和文件
         let log_file = File::create(log_file_path)
             .unwrap_or_else(|e| panic!("无法创建日志文件 {}: {}", log_file_path, e));
 
-        CombinedLogger::init(vec![
-            TermLogger::new(
-                level,
-                log_config.clone(),
-                TerminalMode::Mixed,
-                ColorChoice::Auto,
-            ),
-            WriteLogger::new(level, log_config, log_file),
-        ])
-        .unwrap_or_else(|e| panic!("初始化日志失败: {}", e));
+        CombinedLogger::init(vec![console, WriteLogger::new(level, log_config, log_file)])
+            .unwrap_or_else(|e| panic!("初始化日志失败: {}", e));
 
         log::info!("日志将同时输出到控制台和文件: {}", log_file_path);
     } else {
         // 如果没有指定日志文件，只输出到控制台
-        TermLogger::init(level, log_config, TerminalMode::Mixed, ColorChoice::Auto)
-            .unwrap_or_else(|e| panic!("初始化日志失败: {}", e));
+        CombinedLogger::init(vec![console]).unwrap_or_else(|e| panic!("初始化日志失败: {}", e));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::Level;
+
+    #[test]
+    fn test_json_log_line_escapes_multiline_messages() {
+        let line = format_json(
+            &Record::builder()
+                .args(format_args!("第一行\n第二行 \"quoted\""))
+                .level(Level::Warn)
+                .target("rsendmail_core::mailer")
+                .file(Some("src/mailer.rs"))
+                .line(Some(42))
+                .build(),
+        );
+
+        assert!(!line.contains('\n'));
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["level"], "WARN");
+        assert_eq!(value["message"], "第一行\n第二行 \"quoted\"");
+        assert_eq!(value["target"], "rsendmail_core::mailer");
+        assert_eq!(value["file"], "src/mailer.rs");
+        assert_eq!(value["line"], 42);
+        assert!(chrono::DateTime::parse_from_rfc3339(value["timestamp"].as_str().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_color_choice_mapping() {
+        assert_eq!(color_choice(None, false), ColorChoice::Auto);
+        assert_eq!(color_choice(Some("auto"), false), ColorChoice::Auto);
+        assert_eq!(color_choice(Some("always"), false), ColorChoice::Always);
+        assert_eq!(color_choice(Some("Never"), false), ColorChoice::Never);
+
+        // NO_COLOR turns off automatic color but not an explicit choice
+        assert_eq!(color_choice(None, true), ColorChoice::Never);
+        assert_eq!(color_choice(Some("auto"), true), ColorChoice::Never);
+        assert_eq!(color_choice(Some("always"), true), ColorChoice::Always);
     }
 }
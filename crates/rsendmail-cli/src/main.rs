@@ -1,41 +1,167 @@
-use log::{error, info, warn};
+use log::{error, info, warn, LevelFilter};
 use rsendmail_i18n::{set_language, tr, tr_with_args};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::io::{IsTerminal, Write};
+use std::process::ExitCode;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 mod args;
 mod logging;
+mod progress;
 
-use args::{detect_language, parse_args};
-use rsendmail_core::{Mailer, Stats};
+use args::{detect_language, parse_args, Action};
+use progress::BarProgress;
+use rsendmail_core::checkpoint::Checkpoint;
+use rsendmail_core::send_window::SendWindow;
+use rsendmail_core::{Mailer, NoInputFiles, RunControl, RunState, Stats};
+
+/// Exit code when the input directories matched no files (and --allow-empty is not set)
+const EXIT_NO_INPUT: u8 = 3;
 
 #[tokio::main]
-async fn main() -> anyhow::Result<()> {
+async fn main() -> anyhow::Result<ExitCode> {
     // Detect and set language BEFORE parsing CLI args
     // This ensures --help shows localized text
     let lang = detect_language();
     set_language(lang);
 
     // Parse CLI args with localized help
-    let config = parse_args();
+    let (action, config) = parse_args();
+
+    // Progress bar for interactive EML sends; created first so log lines can be drawn above it
+    let progress = (action == Action::Send
+        && !config.count_only
+        && !config.validate_only
+        && config.print_message.is_none()
+        && progress::enabled(&config, std::io::stdout().is_terminal()))
+    .then(|| Arc::new(BarProgress::new()));
 
     // Initialize logging
-    let log_level = config.get_log_level();
-    logging::init_logging(log_level, config.log_file.as_deref());
+    // Count-only output and printed messages go to stdout, keep progress logs out of them
+    let to_stdout = config.count_only
+        || (config.print_message.is_some() && config.print_message_output.is_none());
+    let log_level = if to_stdout {
+        config.get_log_level().min(LevelFilter::Warn)
+    } else {
+        config.get_log_level()
+    };
+    logging::init_logging(
+        log_level,
+        config.log_file.as_deref(),
+        &config.log_format,
+        config.color.as_deref(),
+        progress.as_ref().map(|progress| progress.bar().clone()),
+    );
 
-    // Create atomic bool for graceful shutdown
-    let running = Arc::new(AtomicBool::new(true));
+    // Shared run state for two-phase shutdown
+    let running = RunControl::new();
     let r = running.clone();
 
-    // Setup Ctrl+C handler
-    ctrlc::set_handler(move || {
-        warn!("{}", tr("cli_main.interrupted"));
-        r.store(false, Ordering::SeqCst);
+    // Setup Ctrl+C handler: first press drains in-flight emails, second press aborts
+    ctrlc::set_handler(move || match r.request_stop() {
+        RunState::Draining => warn!("{}", tr("cli_main.interrupted")),
+        _ => warn!("{}", tr("cli_main.aborting")),
     })?;
 
+    // Print only the number of matching files so the output can be captured by scripts
+    if config.count_only {
+        let (files, total_bytes) = Mailer::new(config.clone()).count_files()?;
+        println!("{}", files);
+        eprintln!(
+            "{}",
+            tr_with_args(
+                "cli_main.count_only_bytes",
+                &[("count", &files.to_string()), ("bytes", &total_bytes.to_string())]
+            )
+        );
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // Check that the server is reachable and accepts our credentials, then stop
+    if action == Action::Test {
+        let report = Mailer::new(config.clone()).test_connection().await?;
+        info!("{}", report);
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // Preflight summary only
+    if action == Action::Estimate {
+        info!("{}", Mailer::new(config.clone()).estimate()?);
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // Lint the EML files and stop before connecting; any problem fails the run
+    if action == Action::Validate || config.validate_only {
+        let report = Mailer::new(config.clone()).validate()?;
+        info!("{}", report);
+        if !report.is_clean() {
+            anyhow::bail!(tr_with_args(
+                "cli_main.validation_failed",
+                &[("count", &report.reports.len().to_string())]
+            ));
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // Dump the exact DATA payload for one file and stop before connecting
+    if config.print_message.is_some() {
+        let (file, payload) = Mailer::new(config.clone()).render_message()?;
+        match &config.print_message_output {
+            Some(path) => {
+                std::fs::write(path, &payload)?;
+                info!(
+                    "{}",
+                    tr_with_args("cli_main.print_message_written", &[("file", &file), ("path", path)])
+                );
+            }
+            None => std::io::stdout().write_all(&payload)?,
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // Truncate the checkpoint once, before any round, so later rounds still skip sent files
+    if let (true, Some(path)) = (config.reset_checkpoint, &config.checkpoint_file) {
+        Checkpoint::reset(path)?;
+        info!(
+            "{}",
+            tr_with_args("cli_main.checkpoint_reset", &[("path", path)])
+        );
+    }
+
+    // Daily send window, checked before every round
+    let send_window = match config.send_window.as_deref() {
+        Some(window) => Some(SendWindow::parse(window).ok_or_else(|| {
+            anyhow::anyhow!(tr_with_args(
+                "cli_main.invalid_send_window",
+                &[("window", window)]
+            ))
+        })?),
+        None => None,
+    };
+
     // Create mailer
-    let mailer = Mailer::new(config.clone());
+    let mailer = match &progress {
+        Some(progress) => Mailer::new_with_progress(config.clone(), progress.clone()),
+        None => Mailer::new(config.clone()),
+    };
+
+    // Catch a mistyped path or extension before sending. Checked once against the raw
+    // directory scan, so a resumed checkpoint or a loop round with nothing new is not an error
+    if let Err(e) = mailer.check_input_files() {
+        return match exit_code(&e) {
+            Some(code) => Ok(ExitCode::from(code)),
+            None => Err(e),
+        };
+    }
+
+    // Preflight summary, sending starts only after an explicit yes
+    if config.confirm {
+        info!("{}", mailer.estimate()?);
+        if !confirm(&tr("cli_main.confirm_prompt"))? {
+            info!("{}", tr("cli_main.confirm_declined"));
+            return Ok(ExitCode::SUCCESS);
+        }
+    }
 
     // Set iteration count
     let mut iteration_count = if config.r#loop {
@@ -48,10 +174,34 @@ async fn main() -> anyhow::Result<()> {
     let mut total_stats = Stats::new();
     let total_start_time = Instant::now();
     let mut successful_iterations = 0;
+    // Consecutive failed rounds, drives the retry backoff
+    let mut consecutive_failures: u32 = 0;
 
     // Main send loop
     let mut current_iteration = 1;
-    while iteration_count > 0 && running.load(Ordering::SeqCst) {
+    while iteration_count > 0 && running.is_running() {
+        // Outside the send window: pause until it reopens (or the run is stopped)
+        if let Some(window) = &send_window {
+            let wait = window.wait_from(chrono::Local::now().time());
+            if !wait.is_zero() {
+                info!(
+                    "{}",
+                    tr_with_args(
+                        "cli_main.send_window_wait",
+                        &[
+                            ("window", config.send_window.as_deref().unwrap_or_default()),
+                            ("seconds", &wait.as_secs().to_string()),
+                            ("start", &window.start.format("%H:%M").to_string())
+                        ]
+                    )
+                );
+                tokio::select! {
+                    _ = tokio::time::sleep(wait) => {}
+                    _ = running.stopped() => break,
+                }
+            }
+        }
+
         let total_str = if config.r#loop {
             "∞".to_string()
         } else {
@@ -68,41 +218,18 @@ async fn main() -> anyhow::Result<()> {
             )
         );
 
+        if let Some(progress) = &progress {
+            let total = mailer.count_files().map(|(files, _)| files).unwrap_or_default();
+            progress.start_round(total as u64);
+        }
+
         // Send emails and get stats
         match mailer.send_all_with_cancel(running.clone()).await {
             Ok(stats) => {
                 successful_iterations += 1;
+                consecutive_failures = 0;
 
-                // Accumulate stats
-                total_stats.email_count += stats.email_count;
-
-                for duration in &stats.parse_durations {
-                    total_stats.parse_durations.push(*duration);
-                }
-                for duration in &stats.send_durations {
-                    total_stats.send_durations.push(*duration);
-                }
-
-                total_stats.parse_errors += stats.parse_errors;
-                total_stats.send_errors += stats.send_errors;
-
-                // Accumulate error details
-                for (error_type, count) in &stats.error_details {
-                    let entry = total_stats
-                        .error_details
-                        .entry(error_type.clone())
-                        .or_insert(0);
-                    *entry += count;
-                }
-
-                // Accumulate failed files
-                for (error_type, files) in &stats.failed_files {
-                    total_stats
-                        .failed_files
-                        .entry(error_type.clone())
-                        .or_default()
-                        .extend(files.clone());
-                }
+                total_stats.merge(&stats);
 
                 info!(
                     "{}",
@@ -114,7 +241,7 @@ async fn main() -> anyhow::Result<()> {
                 info!("{}", stats);
 
                 // Wait before next iteration if not the last one
-                if iteration_count > 1 && running.load(Ordering::SeqCst) {
+                if iteration_count > 1 && running.is_running() {
                     info!(
                         "{}",
                         tr_with_args(
@@ -137,18 +264,20 @@ async fn main() -> anyhow::Result<()> {
                     )
                 );
                 // Continue if in loop mode and not interrupted
-                if !config.r#loop || !running.load(Ordering::SeqCst) {
+                if !config.r#loop || !running.is_running() {
                     return Err(e);
                 }
-                // Wait and retry
+                // Wait and retry (grows with consecutive failures when backoff is enabled)
+                let retry_delay = config.retry_delay(consecutive_failures);
+                consecutive_failures = consecutive_failures.saturating_add(1);
                 info!(
                     "{}",
                     tr_with_args(
                         "cli_main.waiting_next_round",
-                        &[("seconds", &config.retry_interval.to_string())]
+                        &[("seconds", &format!("{:.1}", retry_delay.as_secs_f64()))]
                     )
                 );
-                tokio::time::sleep(Duration::from_secs(config.retry_interval)).await;
+                tokio::time::sleep(retry_delay).await;
             }
         }
 
@@ -156,6 +285,10 @@ async fn main() -> anyhow::Result<()> {
         iteration_count -= 1;
     }
 
+    if let Some(progress) = &progress {
+        progress.finish();
+    }
+
     // Show overall stats
     if successful_iterations > 0 {
         total_stats.total_duration = total_start_time.elapsed();
@@ -167,7 +300,90 @@ async fn main() -> anyhow::Result<()> {
             )
         );
         info!("{}", total_stats);
+
+        if let Some(ref path) = config.stats_json {
+            match total_stats.write_json(path) {
+                Ok(()) => info!(
+                    "{}",
+                    tr_with_args("cli_main.stats_json_written", &[("path", path)])
+                ),
+                Err(e) => error!(
+                    "{}",
+                    tr_with_args(
+                        "cli_main.stats_json_failed",
+                        &[("path", path), ("error", &e.to_string())]
+                    )
+                ),
+            }
+        }
+
+        if let Some(ref path) = config.failed_csv {
+            match total_stats.write_failed_csv(path) {
+                Ok(()) => info!(
+                    "{}",
+                    tr_with_args("cli_main.failed_csv_written", &[("path", path)])
+                ),
+                Err(e) => error!(
+                    "{}",
+                    tr_with_args(
+                        "cli_main.failed_csv_failed",
+                        &[("path", path), ("error", &e.to_string())]
+                    )
+                ),
+            }
+        }
+
+        if let Some(ref report_to) = config.report_to {
+            match mailer.send_report(&total_stats).await {
+                // Dry runs skip the report; the core logs why
+                Ok(()) if config.dry_run => {}
+                Ok(()) => info!(
+                    "{}",
+                    tr_with_args("cli_main.report_sent", &[("to", report_to)])
+                ),
+                Err(e) => error!(
+                    "{}",
+                    tr_with_args(
+                        "cli_main.report_failed",
+                        &[("to", report_to), ("error", &e.to_string())]
+                    )
+                ),
+            }
+        }
     }
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Distinct exit code for run errors that scripts should tell apart from a failed send;
+/// None falls back to returning the error (exit code 1)
+fn exit_code(error: &anyhow::Error) -> Option<u8> {
+    error.is::<NoInputFiles>().then_some(EXIT_NO_INPUT)
+}
+
+/// Print `prompt` and read a line from stdin; only "y" or "yes" counts as consent
+fn confirm(prompt: &str) -> std::io::Result<bool> {
+    print!("{}", prompt);
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(
+        answer.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_input_has_distinct_exit_code() {
+        let empty = anyhow::Error::new(NoInputFiles {
+            dir: "/data/mails".to_string(),
+            extension: "eml".to_string(),
+        });
+        assert_eq!(exit_code(&empty), Some(EXIT_NO_INPUT));
+        assert_eq!(exit_code(&anyhow::anyhow!("connection refused")), None);
+    }
 }
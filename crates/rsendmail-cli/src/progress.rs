@@ -0,0 +1,106 @@
+//! Terminal progress bar for EML batch sends
+//!
+//! The bar is driven through [`SendProgress`]: every send worker reports each finished
+//! email, which bumps a shared counter mirrored as the bar position. Log lines are
+//! written through [`ProgressBar::suspend`] (see `logging::init_logging`) so they are
+//! printed above the bar instead of over it.
+
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use rsendmail_core::{Config, SendProgress};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// The bar is only drawn when requested, for EML batch sends, with text logs, on an interactive stdout
+pub fn enabled(config: &Config, stdout_is_terminal: bool) -> bool {
+    config.progress
+        && stdout_is_terminal
+        && !config.log_format.eq_ignore_ascii_case("json")
+        && config.attachment.is_none()
+        && config.attachment_dir.is_none()
+}
+
+/// Files sent / total, send rate and ETA for the current round
+pub struct BarProgress {
+    bar: ProgressBar,
+    completed: AtomicU64,
+}
+
+impl BarProgress {
+    pub fn new() -> Self {
+        let bar = ProgressBar::with_draw_target(Some(0), ProgressDrawTarget::stderr());
+        bar.set_style(
+            ProgressStyle::with_template("{wide_bar} {pos}/{len} {per_sec} ETA {eta}")
+                .expect("valid progress template"),
+        );
+        Self {
+            bar,
+            completed: AtomicU64::new(0),
+        }
+    }
+
+    pub fn bar(&self) -> &ProgressBar {
+        &self.bar
+    }
+
+    /// Restart the bar for a round of `total` files
+    pub fn start_round(&self, total: u64) {
+        self.completed.store(0, Ordering::Relaxed);
+        self.bar.reset();
+        self.bar.set_length(total);
+    }
+
+    /// Remove the bar once sending is over
+    pub fn finish(&self) {
+        self.bar.finish_and_clear();
+    }
+
+    fn complete_one(&self) {
+        let done = self.completed.fetch_add(1, Ordering::Relaxed) + 1;
+        self.bar.set_position(done);
+    }
+}
+
+impl SendProgress for BarProgress {
+    fn on_email_sent(&self, _path: &str, _duration: Duration) {
+        self.complete_one();
+    }
+
+    fn on_email_failed(&self, _path: &str, _error: &str) {
+        self.complete_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bar_disabled_without_terminal_or_with_json_logs() {
+        let config = Config {
+            progress: true,
+            ..Config::default()
+        };
+        assert!(enabled(&config, true));
+        assert!(!enabled(&config, false));
+
+        let json = Config {
+            log_format: "json".to_string(),
+            ..config.clone()
+        };
+        assert!(!enabled(&json, true));
+        assert!(!enabled(&Config::default(), true));
+    }
+
+    #[test]
+    fn test_completed_emails_advance_the_bar() {
+        let progress = BarProgress::new();
+        progress.start_round(3);
+        progress.on_email_sent("a.eml", Duration::ZERO);
+        progress.on_email_failed("b.eml", "550");
+        assert_eq!(progress.bar().position(), 2);
+        assert_eq!(progress.bar().length(), Some(3));
+
+        progress.start_round(1);
+        assert_eq!(progress.bar().position(), 0);
+    }
+}
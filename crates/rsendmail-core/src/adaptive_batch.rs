@@ -0,0 +1,77 @@
+//! 根据服务器应答自动调整批次大小（AIMD）
+//!
+//! 启用 `adaptive_batch` 后，每个进程组以 `batch_size` 为初始批次大小：批次全部发送成功
+//! 时加一，批次中出现421等需要重置连接的错误或4xx临时性失败时减半，始终保持在
+//! `adaptive_batch_min` 与 `adaptive_batch_max` 之间。出错后仍会重新逐步增大，
+//! 服务器的限制放宽时批次大小随之回升。连接在批次之间照常复用，只有421等错误才重置连接。
+
+use crate::config::Config;
+
+/// 单个进程组当前的批次大小
+#[derive(Debug)]
+pub struct AdaptiveBatch {
+    size: usize,
+    min: usize,
+    max: usize,
+}
+
+impl AdaptiveBatch {
+    /// 未启用 `adaptive_batch` 时返回 None
+    pub fn from_config(config: &Config) -> Option<Self> {
+        config.adaptive_batch.then(|| {
+            let min = config.adaptive_batch_min.max(1);
+            let max = config.adaptive_batch_max.max(min);
+            Self {
+                size: config.batch_size.clamp(min, max),
+                min,
+                max,
+            }
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// 记录一个批次的结果，返回调整后的批次大小（未变化时返回 None）
+    ///
+    /// `backoff` 为批次中出现了需要重置连接的错误或临时性失败。
+    pub fn record(&mut self, backoff: bool) -> Option<usize> {
+        let previous = self.size;
+        self.size = if backoff {
+            (self.size / 2).max(self.min)
+        } else {
+            (self.size + 1).min(self.max)
+        };
+        (self.size != previous).then_some(self.size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grows_by_one_and_halves_within_range() {
+        let config = Config {
+            adaptive_batch: true,
+            adaptive_batch_min: 2,
+            adaptive_batch_max: 5,
+            batch_size: 4,
+            ..Config::default()
+        };
+        let mut batch = AdaptiveBatch::from_config(&config).unwrap();
+
+        assert_eq!(batch.record(false), Some(5));
+        assert_eq!(batch.record(false), None);
+        assert_eq!(batch.record(true), Some(2));
+        assert_eq!(batch.record(false), Some(3));
+        assert_eq!(batch.record(false), Some(4));
+        // 出错不会永久压低上限，成功后仍可回到 adaptive_batch_max
+        assert_eq!(batch.record(false), Some(5));
+        assert_eq!(batch.record(true), Some(2));
+        assert_eq!(batch.record(true), None);
+        assert_eq!(batch.size(), 2);
+        assert!(AdaptiveBatch::from_config(&Config::default()).is_none());
+    }
+}
@@ -1,12 +1,29 @@
+use hmac::{Hmac, Mac};
 use log::debug;
 use rand::{distributions::Alphanumeric, Rng};
+use regex::bytes::Regex as BytesRegex;
 use regex::Regex;
+use sha2::Sha256;
 use std::collections::HashMap;
 
+use crate::config::Config;
+
+// 常见电话号码格式：+86 138 0013 8000、(555) 123-4567、555-123-4567、13800138000
+const PHONE_PATTERN: &str =
+    r"(?:\+\d{1,3}[ .-]?)?(?:\(\d{2,4}\)[ .-]?|\b\d{2,4}[ .-])\d{3,4}[ .-]?\d{4}\b|\b1[3-9]\d{9}\b";
+
+// 点分十进制IPv4地址，每段 0-255
+const IPV4_PATTERN: &str =
+    r"\b(?:(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)\.){3}(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)\b";
+
 pub struct EmailAnonymizer {
     email_regex: Regex,
+    anonymize_emails: bool,
+    phone_regex: Option<Regex>,
+    ip_regex: Option<Regex>,
     map: HashMap<String, String>,
     target_domain: String,
+    key: Option<Vec<u8>>,
 }
 
 impl EmailAnonymizer {
@@ -14,40 +31,172 @@ impl EmailAnonymizer {
         Self {
             // 匹配大多数标准格式的邮箱
             email_regex: Regex::new(r"[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}").unwrap(),
+            anonymize_emails: true,
+            phone_regex: None,
+            ip_regex: None,
             map: HashMap::new(),
             target_domain: target_domain.to_string(),
+            key: None,
         }
     }
 
+    /// 按配置创建匿名化器；邮箱、电话、IP 均未开启匿名化时返回 None
+    pub fn from_config(config: &Config) -> Option<Self> {
+        if !(config.anonymize_emails || config.anonymize_phones || config.anonymize_ips) {
+            return None;
+        }
+        Some(
+            Self::new(&config.anonymize_domain)
+                .with_emails(config.anonymize_emails)
+                .with_phones(config.anonymize_phones)
+                .with_ips(config.anonymize_ips)
+                .with_key(config.anonymize_key.as_deref()),
+        )
+    }
+
+    /// 是否替换邮箱地址（默认开启）
+    pub fn with_emails(mut self, enabled: bool) -> Self {
+        self.anonymize_emails = enabled;
+        self
+    }
+
+    /// 是否替换电话号码，替换结果保留原格式，仅数字随机化
+    pub fn with_phones(mut self, enabled: bool) -> Self {
+        self.phone_regex = enabled.then(|| Regex::new(PHONE_PATTERN).unwrap());
+        self
+    }
+
+    /// 是否替换IPv4地址，替换结果位于 10.0.0.0/8 私有网段
+    pub fn with_ips(mut self, enabled: bool) -> Self {
+        self.ip_regex = enabled.then(|| Regex::new(IPV4_PATTERN).unwrap());
+        self
+    }
+
+    /// 设置后邮箱用户名由 HMAC-SHA256(key, 原地址) 截断生成，跨运行保持一致
+    pub fn with_key(mut self, key: Option<&str>) -> Self {
+        self.key = key.map(|k| k.as_bytes().to_vec());
+        self
+    }
+
     // 对文本内容进行匿名化处理
     pub fn anonymize_text(&mut self, text: &str) -> String {
         let mut result = text.to_string();
 
-        // 找出所有匹配的邮箱地址
-        let matches: Vec<_> = self
-            .email_regex
+        if self.anonymize_emails {
+            // 找出所有匹配的邮箱地址
+            let matches: Vec<_> = self
+                .email_regex
+                .find_iter(text)
+                .map(|cap| (cap.start(), cap.end(), cap.as_str().to_string()))
+                .collect();
+
+            // 对每个邮箱地址进行匿名化处理
+            for (_, _, email) in matches {
+                let anonymized = self.get_anonymized_email(&email);
+                result = result.replace(&email, &anonymized);
+            }
+        }
+
+        // IP 先于电话处理，避免电话规则误匹配点分地址
+        if let Some(regex) = self.ip_regex.clone() {
+            result = self.replace_with_pseudonyms(&regex, &result, random_ipv4);
+        }
+        if let Some(regex) = self.phone_regex.clone() {
+            result = self.replace_with_pseudonyms(&regex, &result, random_phone);
+        }
+
+        result
+    }
+
+    // 将 `regex` 的每个匹配替换为稳定的假名，同一原值始终映射到同一替换结果
+    fn replace_with_pseudonyms(
+        &mut self,
+        regex: &Regex,
+        text: &str,
+        generate: fn(&str) -> String,
+    ) -> String {
+        let matches: Vec<String> = regex
             .find_iter(text)
-            .map(|cap| (cap.start(), cap.end(), cap.as_str().to_string()))
+            .map(|m| m.as_str().to_string())
             .collect();
 
-        // 对每个邮箱地址进行匿名化处理
-        for (_, _, email) in matches {
-            let anonymized = self.get_anonymized_email(&email);
-            result = result.replace(&email, &anonymized);
+        let mut result = text.to_string();
+        for original in matches {
+            let replacement = self.get_pseudonym(&original, generate);
+            result = result.replace(&original, &replacement);
         }
-
         result
     }
 
+    // 获取或生成电话号码/IP地址的假名
+    fn get_pseudonym(&mut self, original: &str, generate: fn(&str) -> String) -> String {
+        self.map
+            .entry(original.to_string())
+            .or_insert_with(|| {
+                let pseudonym = generate(original);
+                debug!("匿名化: {} -> {}", original, pseudonym);
+                pseudonym
+            })
+            .clone()
+    }
+
     // 对二进制内容（如邮件文件）进行匿名化处理
     pub fn anonymize_binary(&mut self, content: &[u8]) -> Vec<u8> {
-        let text = match std::str::from_utf8(content) {
-            Ok(s) => s,
-            Err(_) => return content.to_vec(), // 如果无法解析为UTF-8，则返回原内容
-        };
+        match std::str::from_utf8(content) {
+            Ok(text) => self.anonymize_text(text).into_bytes(),
+            // 正文为GBK、Latin-1等非UTF-8编码时，逐字节扫描ASCII范围内的匹配项，
+            // 其余字节原样保留，头部中的地址同样会被替换
+            Err(_) => self.anonymize_ascii_bytes(content),
+        }
+    }
+
+    // 非UTF-8内容的匿名化：规则与 anonymize_text 相同，但以 ASCII 语义匹配字节
+    fn anonymize_ascii_bytes(&mut self, content: &[u8]) -> Vec<u8> {
+        let mut result = content.to_vec();
+        if self.anonymize_emails {
+            let pattern = self.email_regex.as_str().to_string();
+            result = self.replace_bytes(&pattern, &result, Self::get_anonymized_email);
+        }
+        if let Some(pattern) = self.ip_regex.as_ref().map(|r| r.as_str().to_string()) {
+            result = self.replace_bytes(&pattern, &result, |this, ip| {
+                this.get_pseudonym(ip, random_ipv4)
+            });
+        }
+        if let Some(pattern) = self.phone_regex.as_ref().map(|r| r.as_str().to_string()) {
+            result = self.replace_bytes(&pattern, &result, |this, phone| {
+                this.get_pseudonym(phone, random_phone)
+            });
+        }
+        result
+    }
 
-        let anonymized = self.anonymize_text(text);
-        anonymized.into_bytes()
+    fn replace_bytes(
+        &mut self,
+        pattern: &str,
+        content: &[u8],
+        mut pseudonym: impl FnMut(&mut Self, &str) -> String,
+    ) -> Vec<u8> {
+        // (?-u)：\d、\b 等按ASCII解释，允许匹配任意字节序列
+        let regex = BytesRegex::new(&format!("(?-u){}", pattern)).unwrap();
+        let mut replacements = HashMap::new();
+        for m in regex.find_iter(content) {
+            // 模式只包含ASCII字符，匹配结果必然是合法UTF-8
+            if let Ok(original) = std::str::from_utf8(m.as_bytes()) {
+                if !replacements.contains_key(original) {
+                    let replacement = pseudonym(self, original);
+                    replacements.insert(original.to_string(), replacement);
+                }
+            }
+        }
+
+        regex
+            .replace_all(content, |caps: &regex::bytes::Captures| {
+                std::str::from_utf8(&caps[0])
+                    .ok()
+                    .and_then(|original| replacements.get(original))
+                    .map_or_else(|| caps[0].to_vec(), |r| r.as_bytes().to_vec())
+            })
+            .into_owned()
     }
 
     // 获取或生成匿名化后的邮箱
@@ -56,14 +205,17 @@ impl EmailAnonymizer {
             return anonymized.clone();
         }
 
-        // 生成随机字符串作为邮箱用户名部分
-        let random_string: String = rand::thread_rng()
-            .sample_iter(&Alphanumeric)
-            .take(8)
-            .map(|c| c as char)
-            .collect();
+        // 配置了密钥时由密钥派生用户名，否则生成随机字符串
+        let username = match &self.key {
+            Some(key) => keyed_username(key, email),
+            None => rand::thread_rng()
+                .sample_iter(&Alphanumeric)
+                .take(8)
+                .map(|c| c as char)
+                .collect(),
+        };
 
-        let anonymized = format!("{}@{}", random_string, self.target_domain);
+        let anonymized = format!("{}@{}", username, self.target_domain);
 
         debug!("匿名化邮箱: {} -> {}", email, anonymized);
         self.map.insert(email.to_string(), anonymized.clone());
@@ -72,6 +224,41 @@ impl EmailAnonymizer {
     }
 }
 
+// HMAC-SHA256(key, email) 的前8字节，以16位小写十六进制表示
+fn keyed_username(key: &[u8], email: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(email.as_bytes());
+    mac.finalize().into_bytes()[..8]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+// 保留分隔符与 + 号，仅将每位数字替换为随机数字
+fn random_phone(original: &str) -> String {
+    let mut rng = rand::thread_rng();
+    original
+        .chars()
+        .map(|c| {
+            if c.is_ascii_digit() {
+                char::from(b'0' + rng.gen_range(0..10u8))
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+fn random_ipv4(_original: &str) -> String {
+    let mut rng = rand::thread_rng();
+    format!(
+        "10.{}.{}.{}",
+        rng.gen::<u8>(),
+        rng.gen::<u8>(),
+        rng.gen_range(1..255u8)
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -102,4 +289,80 @@ mod tests {
 
         assert!(anonymized2.contains(first_replacement));
     }
+
+    #[test]
+    fn test_anonymize_phones_and_ips_are_stable() {
+        let mut anonymizer = EmailAnonymizer::new("example.com")
+            .with_phones(true)
+            .with_ips(true);
+
+        let text = "电话: +86 138 0013 8000，手机 13912345678，来源 192.168.1.20";
+        let first = anonymizer.anonymize_text(text);
+        assert!(!first.contains("138 0013 8000"));
+        assert!(!first.contains("13912345678"));
+        assert!(!first.contains("192.168.1.20"));
+
+        // 同一号码和地址在第二次调用中映射到相同的替换结果
+        let second = anonymizer.anonymize_text("回拨 +86 138 0013 8000，来源 192.168.1.20");
+        let phone = anonymizer.map.get("+86 138 0013 8000").unwrap();
+        let ip = anonymizer.map.get("192.168.1.20").unwrap();
+        assert!(first.contains(phone.as_str()) && second.contains(phone.as_str()));
+        assert!(first.contains(ip.as_str()) && second.contains(ip.as_str()));
+        assert!(ip.starts_with("10."));
+    }
+
+    #[test]
+    fn test_keyed_anonymization_is_stable_across_instances() {
+        let text = "From: user@domain.com\r\nCc: other@domain.com\r\n";
+        let first = EmailAnonymizer::new("example.com")
+            .with_key(Some("secret"))
+            .anonymize_text(text);
+        let second = EmailAnonymizer::new("example.com")
+            .with_key(Some("secret"))
+            .anonymize_text(text);
+        assert_eq!(first, second);
+        assert!(!first.contains("user@domain.com"));
+
+        // 不同地址、不同密钥得到不同的假名
+        let user = keyed_username(b"secret", "user@domain.com");
+        assert_eq!(user.len(), 16);
+        assert_ne!(user, keyed_username(b"secret", "other@domain.com"));
+        assert_ne!(user, keyed_username(b"other-secret", "user@domain.com"));
+        assert!(first.contains(&format!("{}@example.com", user)));
+    }
+
+    #[test]
+    fn test_phone_and_ip_toggles_off_by_default() {
+        let mut anonymizer = EmailAnonymizer::new("example.com");
+        let text = "user@domain.com 555-123-4567 10.1.2.3";
+        let anonymized = anonymizer.anonymize_text(text);
+        assert!(!anonymized.contains("user@domain.com"));
+        assert!(anonymized.contains("555-123-4567"));
+        assert!(anonymized.contains("10.1.2.3"));
+    }
+
+    #[test]
+    fn test_anonymize_binary_handles_non_utf8_body() {
+        let mut anonymizer = EmailAnonymizer::new("example.com");
+
+        // 头部为ASCII，正文为GBK编码的“你好”加一个邮箱地址
+        let mut content = b"From: sender@corp.com\r\nTo: victim@corp.com\r\n\
+Content-Type: text/plain; charset=gbk\r\n\r\n"
+            .to_vec();
+        content.extend_from_slice(&[0xC4, 0xE3, 0xBA, 0xC3]);
+        content.extend_from_slice(b" victim@corp.com\r\n");
+        assert!(std::str::from_utf8(&content).is_err());
+
+        let anonymized = anonymizer.anonymize_binary(&content);
+        let lossy = String::from_utf8_lossy(&anonymized);
+
+        assert!(!lossy.contains("victim@corp.com"));
+        assert!(!lossy.contains("sender@corp.com"));
+        let replacement = anonymizer.map.get("victim@corp.com").unwrap();
+        assert!(lossy.contains(&format!("To: {}", replacement)));
+        // GBK字节保持不变
+        assert!(anonymized
+            .windows(4)
+            .any(|w| w == [0xC4, 0xE3, 0xBA, 0xC3]));
+    }
 }
@@ -0,0 +1,440 @@
+//! 附件邮件的流式构建
+//!
+//! 附件模式下邮件正文和头部很小，体积几乎全部来自附件本身。若先把附件完整读入
+//! 内存再交给 MessageBuilder 编码，单封邮件的内存占用约为附件大小的 2.4 倍（原始
+//! 内容 + base64 编码后的完整邮件），多个大附件并发发送时很容易耗尽内存。
+//!
+//! 这里只用 MessageBuilder 生成附件内容以外的部分：附件位置放一个唯一占位符，
+//! 渲染后在占位符处拆成前后两段。发送时先写前段，再从磁盘按块读取附件、逐块
+//! base64 编码后直接写入 DATA，最后写后段。单封邮件的内存占用因此固定为一个
+//! 读缓冲区加一个编码缓冲区（约 130KB），与附件大小无关。
+//!
+//! 代价是附件在 DATA 阶段才被读取：读取中途出错时该邮件的 SMTP 会话已无法恢复，
+//! 只能按发送失败处理；文件打开失败仍在发送前报告。
+//!
+//! 开启解压且附件为 gzip 内容时，附件在构建时整体解压到内存，不再流式读取。
+//!
+//! 一封邮件带多个附件时每个附件使用各自的占位符，渲染结果被拆成附件数加一段，
+//! 发送时依次写出各段并在段之间流式写入对应的附件。
+//!
+//! HTML正文以 `cid:` 引用的内嵌图片（[`InlineImage`]）体积较小，整体读入内存，与HTML
+//! 一起放入 multipart/related 部分。
+
+use mail_send::mail_builder::encoders::base64::base64_encode_mime;
+use mail_send::mail_builder::mime::{make_boundary, BodyPart, MimePart};
+use mail_send::mail_builder::MessageBuilder;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::compression;
+
+/// 每次读取的附件字节数，须为 57 的整数倍，使每块恰好编码为完整的 76 字符行
+const CHUNK_SIZE: usize = 57 * 1024;
+
+/// HTML正文中以 `cid:` 引用的内嵌图片
+#[derive(Debug, Clone)]
+pub struct InlineImage {
+    pub cid: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+impl InlineImage {
+    /// 解析 `cid:路径` 并读取图片，MIME类型按内容推断
+    pub fn load(spec: &str) -> io::Result<Self> {
+        let (cid, path) = spec
+            .split_once(':')
+            .map(|(cid, path)| (cid.trim(), path.trim()))
+            .filter(|(cid, path)| !cid.is_empty() && !path.is_empty())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "expected cid:path"))?;
+        let data = std::fs::read(path)?;
+        let content_type = infer::get(&data)
+            .map_or("application/octet-stream", |kind| kind.mime_type())
+            .to_string();
+        Ok(Self {
+            cid: cid.to_string(),
+            content_type,
+            data,
+        })
+    }
+
+    /// `Content-Disposition: inline` 且带 `Content-ID` 的MIME部分
+    fn part(&self) -> MimePart<'_> {
+        MimePart::new(self.content_type.as_str(), BodyPart::Binary(self.data.as_slice().into()))
+            .inline()
+            .cid(self.cid.as_str())
+    }
+}
+
+/// 附件内容按需从磁盘读取的邮件
+pub struct StreamedAttachment {
+    /// 附件内容之间的各段（已做透明处理），比附件数多一段
+    segments: Vec<Vec<u8>>,
+    contents: Vec<Box<dyn AsyncRead + Unpin + Send>>,
+}
+
+impl StreamedAttachment {
+    /// 以 `builder` 中已设置的信头（发件人、收件人、主题等）构建邮件，
+    /// 正文为 `text`（以及可选的 `html`，`inline_images` 只在有HTML时加入），
+    /// 附件内容在发送时从 `path` 读取；`decompress` 为 true 且文件为 gzip 内容时发送解压后的内容
+    pub fn open(
+        builder: MessageBuilder<'_>,
+        text: &str,
+        html: Option<&str>,
+        inline_images: &[InlineImage],
+        path: &Path,
+        filename: &str,
+        decompress: bool,
+    ) -> io::Result<Self> {
+        Self::open_many(builder, text, html, inline_images, &[(path, filename)], decompress)
+    }
+
+    /// 与 `open` 相同，但 `files` 中每个 (路径, 显示文件名) 都作为一个单独的附件，按顺序排列
+    pub fn open_many(
+        builder: MessageBuilder<'_>,
+        text: &str,
+        html: Option<&str>,
+        inline_images: &[InlineImage],
+        files: &[(&Path, &str)],
+        decompress: bool,
+    ) -> io::Result<Self> {
+        let mut contents: Vec<Box<dyn AsyncRead + Unpin + Send>> = Vec::with_capacity(files.len());
+        let mut markers = Vec::with_capacity(files.len());
+        let mut attachment_parts = Vec::with_capacity(files.len());
+        for &(path, filename) in files {
+            let mut file = File::open(path)?;
+            let kind = if decompress && compression::is_gzip_file(&mut file)? {
+                let data = compression::gunzip(file)?;
+                let kind = infer::get(&data);
+                contents.push(Box::new(io::Cursor::new(data)));
+                kind
+            } else {
+                contents.push(Box::new(tokio::fs::File::from_std(file)));
+                infer::get_from_path(path).ok().flatten()
+            };
+            let mime_type = kind.map_or("application/octet-stream", |k| k.mime_type());
+            // 显式声明传输编码后 MessageBuilder 会原样输出内容，占位符不会被编码
+            let marker = make_boundary("_");
+            attachment_parts.push(
+                MimePart::new(mime_type, BodyPart::Binary(marker.as_bytes().to_vec().into()))
+                    .attachment(filename.to_string())
+                    .transfer_encoding("base64"),
+            );
+            markers.push(marker);
+        }
+
+        let text_part = MimePart::new("text/plain", text);
+        let body_part = match html {
+            Some(html) => {
+                let html_part = MimePart::new("text/html", html);
+                let html_part = if inline_images.is_empty() {
+                    html_part
+                } else {
+                    MimePart::new(
+                        "multipart/related",
+                        std::iter::once(html_part)
+                            .chain(inline_images.iter().map(InlineImage::part))
+                            .collect::<Vec<_>>(),
+                    )
+                };
+                MimePart::new("multipart/alternative", vec![text_part, html_part])
+            }
+            None => text_part,
+        };
+        let rendered = builder
+            .body(MimePart::new(
+                "multipart/mixed",
+                std::iter::once(body_part).chain(attachment_parts).collect::<Vec<_>>(),
+            ))
+            .write_to_vec()?;
+
+        let mut segments = Vec::with_capacity(files.len() + 1);
+        let mut rest = &rendered[..];
+        for marker in &markers {
+            let pos = rest
+                .windows(marker.len())
+                .position(|window| window == marker.as_bytes())
+                .ok_or_else(|| io::Error::other("attachment placeholder missing"))?;
+            segments.push(dot_stuff(&rest[..pos]));
+            rest = &rest[pos + marker.len()..];
+        }
+        segments.push(dot_stuff(rest));
+
+        Ok(Self { segments, contents })
+    }
+
+    /// 写出完整的 DATA 内容，包括结尾的 `<CRLF>.<CRLF>`
+    pub async fn write_data<W: AsyncWrite + Unpin>(self, writer: &mut W) -> io::Result<()> {
+        let mut segments = self.segments.iter();
+        let mut chunk = vec![0u8; CHUNK_SIZE];
+        let mut encoded = Vec::with_capacity(CHUNK_SIZE / 57 * 78);
+        for mut content in self.contents {
+            if let Some(segment) = segments.next() {
+                writer.write_all(segment).await?;
+            }
+            loop {
+                let len = read_full(&mut content, &mut chunk).await?;
+                if len == 0 {
+                    break;
+                }
+                encoded.clear();
+                // base64 字符集不含 '.'，编码结果无需透明处理
+                base64_encode_mime(&chunk[..len], &mut encoded, false)?;
+                writer.write_all(&encoded).await?;
+                if len < chunk.len() {
+                    break;
+                }
+            }
+        }
+
+        for segment in segments {
+            writer.write_all(segment).await?;
+        }
+        writer.write_all(b"\r\n.\r\n").await?;
+        writer.flush().await
+    }
+}
+
+/// 读满 `buf` 或读到文件末尾，返回读取的字节数
+async fn read_full<R: AsyncRead + Unpin>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        let n = reader.read(&mut buf[filled..]).await?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(filled)
+}
+
+/// SMTP 透明处理：CR 或 LF 之后的 '.' 前再加一个 '.'，与 mail-send 的 write_message 一致
+fn dot_stuff(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut is_cr_or_lf = false;
+    for &byte in data {
+        if byte == b'.' && is_cr_or_lf {
+            out.push(b'.');
+            is_cr_or_lf = false;
+        } else {
+            is_cr_or_lf = byte == b'\n' || byte == b'\r';
+        }
+        out.push(byte);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mail_parser::{MessageParser, MimeHeaders};
+    use std::io::Write;
+
+    fn sample_bytes(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 31 % 251) as u8).collect()
+    }
+
+    #[tokio::test]
+    async fn test_streamed_attachment_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.bin");
+        // 跨越多个读取块且不是 57 的整数倍，覆盖末尾的补齐
+        let content = sample_bytes(CHUNK_SIZE * 2 + 100);
+        std::fs::write(&path, &content).unwrap();
+
+        let builder = MessageBuilder::new()
+            .from(("", "sender@example.com"))
+            .to("rcpt@example.com")
+            .subject("streamed");
+        let message = StreamedAttachment::open(
+            builder,
+            "line one\n.line two",
+            Some("<p>html</p>"),
+            &[],
+            &path,
+            "data.bin",
+            false,
+        )
+        .unwrap();
+        let mut data = Vec::new();
+        message.write_data(&mut data).await.unwrap();
+
+        assert!(data.ends_with(b"\r\n.\r\n"));
+        let body = &data[..data.len() - 5];
+        assert!(body.windows(3).any(|w| w == b"\n.."));
+        assert!(body
+            .split(|&b| b == b'\n')
+            .all(|line| line.len() <= 78));
+
+        let parsed = MessageParser::default().parse(body).unwrap();
+        assert_eq!(parsed.subject(), Some("streamed"));
+        assert_eq!(parsed.body_html(0).unwrap().trim(), "<p>html</p>");
+        let attachment = parsed.attachment(0).unwrap();
+        assert_eq!(attachment.attachment_name(), Some("data.bin"));
+        assert_eq!(attachment.contents(), &content[..]);
+    }
+
+    #[tokio::test]
+    async fn test_open_many_builds_one_part_per_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let names = ["report.pdf", "appendix-a.csv", "appendix-b.txt"];
+        let contents: Vec<Vec<u8>> = (1..=3).map(|n| sample_bytes(n * 1000)).collect();
+        let paths: Vec<_> = names
+            .iter()
+            .zip(&contents)
+            .map(|(name, content)| {
+                let path = dir.path().join(name);
+                std::fs::write(&path, content).unwrap();
+                path
+            })
+            .collect();
+        let files: Vec<(&Path, &str)> = paths
+            .iter()
+            .zip(names)
+            .map(|(path, name)| (path.as_path(), name))
+            .collect();
+
+        let message = StreamedAttachment::open_many(
+            MessageBuilder::new(),
+            "see attached",
+            None,
+            &[],
+            &files,
+            false,
+        )
+        .unwrap();
+        let mut data = Vec::new();
+        message.write_data(&mut data).await.unwrap();
+
+        let parsed = MessageParser::default().parse(&data[..data.len() - 5]).unwrap();
+        assert_eq!(parsed.body_text(0).unwrap().trim(), "see attached");
+        assert_eq!(parsed.attachment_count(), 3);
+        for (n, (name, content)) in names.iter().zip(&contents).enumerate() {
+            let attachment = parsed.attachment(n as u32).unwrap();
+            assert_eq!(attachment.attachment_name(), Some(*name));
+            assert_eq!(attachment.contents(), &content[..]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_inline_image_is_related_to_html() {
+        let dir = tempfile::tempdir().unwrap();
+        let image_path = dir.path().join("logo.png");
+        // PNG 文件头，足以让 infer 识别类型
+        std::fs::write(&image_path, b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").unwrap();
+        let attachment_path = dir.path().join("report.txt");
+        std::fs::write(&attachment_path, b"report").unwrap();
+        let logo =
+            InlineImage::load(&format!("logo@example.com:{}", image_path.display())).unwrap();
+        assert!(InlineImage::load("no-separator").is_err());
+
+        let message = StreamedAttachment::open(
+            MessageBuilder::new(),
+            "text",
+            Some(r#"<img src="cid:logo@example.com">"#),
+            std::slice::from_ref(&logo),
+            &attachment_path,
+            "report.txt",
+            false,
+        )
+        .unwrap();
+        let mut data = Vec::new();
+        message.write_data(&mut data).await.unwrap();
+
+        let parsed = MessageParser::default().parse(&data[..data.len() - 5]).unwrap();
+        let html = parsed.body_html(0).unwrap();
+        let cid = html.split("cid:").nth(1).unwrap().split('"').next().unwrap();
+        let image = parsed
+            .parts
+            .iter()
+            .find(|part| part.content_id().is_some())
+            .unwrap();
+        assert_eq!(image.content_id(), Some(cid));
+        assert_eq!(image.content_type().unwrap().subtype(), Some("png"));
+        assert_eq!(image.content_disposition().unwrap().ctype(), "inline");
+        // mail-parser 把内嵌图片也计入附件，真正的附件排在其后
+        let report = parsed.attachment(parsed.attachment_count() as u32 - 1).unwrap();
+        assert_eq!(report.attachment_name(), Some("report.txt"));
+    }
+
+    #[test]
+    fn test_open_reports_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = StreamedAttachment::open(
+            MessageBuilder::new(),
+            "text",
+            None,
+            &[],
+            &dir.path().join("missing.bin"),
+            "missing.bin",
+            false,
+        );
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::NotFound);
+    }
+
+    /// 读取进程的峰值常驻内存（VmHWM，单位 KB）
+    fn peak_rss_kb() -> u64 {
+        let status = std::fs::read_to_string("/proc/self/status").unwrap();
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix("VmHWM:"))
+            .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())
+            .unwrap()
+    }
+
+    /// 将峰值常驻内存重置为当前值（Linux 4.0+）
+    fn reset_peak_rss() {
+        std::fs::write("/proc/self/clear_refs", "5").unwrap();
+    }
+
+    /// 比较 100MB 附件在流式与整体缓冲两种方式下的峰值内存。
+    /// 峰值内存是进程级指标，需单独运行：
+    /// `cargo test -p rsendmail-core --lib --release -- --ignored --nocapture peak_rss`
+    #[tokio::test]
+    #[ignore]
+    async fn bench_peak_rss_streamed_vs_buffered() {
+        const SIZE: usize = 100 * 1024 * 1024;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("large.bin");
+        {
+            let mut file = io::BufWriter::new(File::create(&path).unwrap());
+            let block = sample_bytes(1024 * 1024);
+            for _ in 0..SIZE / block.len() {
+                file.write_all(&block).unwrap();
+            }
+        }
+
+        reset_peak_rss();
+        let baseline = peak_rss_kb();
+        let message = StreamedAttachment::open(
+            MessageBuilder::new(),
+            "text",
+            None,
+            &[],
+            &path,
+            "large.bin",
+            false,
+        )
+        .unwrap();
+        message.write_data(&mut tokio::io::sink()).await.unwrap();
+        let streamed = peak_rss_kb().saturating_sub(baseline);
+
+        reset_peak_rss();
+        let baseline = peak_rss_kb();
+        let content = std::fs::read(&path).unwrap();
+        let rendered = MessageBuilder::new()
+            .text_body("text")
+            .attachment("application/octet-stream", "large.bin", &content[..])
+            .write_to_vec()
+            .unwrap();
+        tokio::io::sink().write_all(&rendered).await.unwrap();
+        drop((content, rendered));
+        let buffered = peak_rss_kb().saturating_sub(baseline);
+
+        println!("peak RSS increase: streamed {streamed} KB, buffered {buffered} KB");
+        assert!(streamed < 16 * 1024, "streamed peak {streamed} KB");
+        assert!(buffered > (SIZE / 1024) as u64);
+    }
+}
@@ -0,0 +1,56 @@
+//! 轮次失败后的重试等待时间计算（指数退避 + 随机抖动）
+
+use rand::Rng;
+use std::time::Duration;
+
+/// 抖动幅度：在计算出的等待时间基础上随机浮动 ±20%
+const JITTER_RATIO: f64 = 0.2;
+
+/// 计算第 `attempt` 次连续失败后的等待时间（不含抖动）
+///
+/// `attempt` 从 0 开始：0 返回 `base`，之后每次翻倍（base, 2x, 4x, …），
+/// 结果不超过 `max`。成功一轮后调用方应将 `attempt` 重置为 0。
+pub fn next_backoff(attempt: u32, base: u64, max: u64) -> Duration {
+    let factor = 1u64.checked_shl(attempt).unwrap_or(u64::MAX);
+    Duration::from_secs(base.saturating_mul(factor).min(max.max(base)))
+}
+
+/// 为等待时间加上 ±20% 的随机抖动，避免多个客户端同时重连
+pub fn with_jitter(delay: Duration) -> Duration {
+    let factor = rand::thread_rng().gen_range((1.0 - JITTER_RATIO)..=(1.0 + JITTER_RATIO));
+    delay.mul_f64(factor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_backoff_grows_and_caps() {
+        assert_eq!(next_backoff(0, 5, 60), Duration::from_secs(5));
+        assert_eq!(next_backoff(1, 5, 60), Duration::from_secs(10));
+        assert_eq!(next_backoff(2, 5, 60), Duration::from_secs(20));
+        assert_eq!(next_backoff(3, 5, 60), Duration::from_secs(40));
+        assert_eq!(next_backoff(4, 5, 60), Duration::from_secs(60));
+        // 大量连续失败时不会溢出，仍停留在上限
+        assert_eq!(next_backoff(200, 5, 60), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_next_backoff_reset_returns_base() {
+        // 成功一轮后计数归零，等待时间回到基础间隔
+        assert_eq!(next_backoff(0, 5, 60), next_backoff(0, 5, 600));
+        // 上限小于基础间隔时按基础间隔处理
+        assert_eq!(next_backoff(3, 30, 10), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_with_jitter_stays_within_range() {
+        let delay = Duration::from_secs(10);
+        for _ in 0..100 {
+            let jittered = with_jitter(delay);
+            assert!(jittered >= Duration::from_secs(8));
+            assert!(jittered <= Duration::from_secs(12));
+        }
+    }
+}
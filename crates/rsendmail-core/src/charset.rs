@@ -0,0 +1,83 @@
+//! 重建邮件时正文使用的输出字符集
+//!
+//! `output_charset` 指定 `modify_headers` 重建邮件时 text/plain 与 text/html 正文的字符集
+//! （如 `gb2312`、`iso-8859-1`），供只能处理旧式字符集的下游系统使用。配置值按 WHATWG
+//! 编码标签解析，`gb2312` 实际为 GBK、`iso-8859-1` 实际为 windows-1252，Content-Type
+//! 声明的是实际使用的编码名称，而不是配置值，避免声明与字节不符。正文按该编码写入，传输
+//! 编码由 mail-builder 按内容选择 quoted-printable 或 base64。目标字符集无法表示的字符被
+//! 替换为HTML数字字符引用。
+//!
+//! 每个配置值只在第一次使用时解析，之后所有进程组共享解析结果。
+
+use anyhow::{anyhow, Result};
+use encoding_rs::Encoding;
+use mail_send::mail_builder::headers::content_type::ContentType;
+use mail_send::mail_builder::mime::{BodyPart, MimePart};
+use rsendmail_i18n::tr_with_args;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use crate::config::Config;
+
+/// 已确认可以用于编码的输出字符集
+#[derive(Debug, Clone, Copy)]
+pub struct OutputCharset {
+    encoding: &'static Encoding,
+}
+
+impl OutputCharset {
+    /// 返回 `output_charset` 对应的字符集，首次调用时解析；未配置时返回 None，字符集不受支持时报错
+    pub fn from_config(config: &Config) -> Result<Option<Self>> {
+        static CHARSETS: OnceLock<Mutex<HashMap<String, OutputCharset>>> = OnceLock::new();
+
+        let Some(label) = config.output_charset.as_deref() else {
+            return Ok(None);
+        };
+        let mut charsets = CHARSETS.get_or_init(Default::default).lock().unwrap();
+        if let Some(charset) = charsets.get(label) {
+            return Ok(Some(*charset));
+        }
+        let charset = Self::parse(label)?;
+        charsets.insert(label.to_string(), charset);
+        Ok(Some(charset))
+    }
+
+    /// 按 WHATWG 编码标签解析字符集；只能解码不能编码的字符集（如 UTF-16）视为不受支持
+    pub fn parse(label: &str) -> Result<Self> {
+        match Encoding::for_label(label.trim().as_bytes()) {
+            Some(encoding) if encoding.output_encoding() == encoding => Ok(Self { encoding }),
+            _ => Err(anyhow!(tr_with_args(
+                "core.mailer.unsupported_charset",
+                &[("charset", label)]
+            ))),
+        }
+    }
+
+    /// Content-Type 中声明的名称，即实际使用的编码名称（如 `gb2312` 为 `GBK`）
+    pub fn name(&self) -> &'static str {
+        self.encoding.name()
+    }
+
+    /// 将正文按该字符集编码为一个 `content_type`（text/plain 或 text/html）的MIME部分
+    pub fn body_part(&self, content_type: &'static str, text: &str) -> MimePart<'static> {
+        let (bytes, _, _) = self.encoding.encode(text);
+        MimePart::new(
+            ContentType::new(content_type).attribute("charset", self.name()),
+            BodyPart::Binary(bytes.into_owned().into()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_accepts_encodable_charsets_only() {
+        assert_eq!(OutputCharset::parse(" GB2312 ").unwrap().name(), "GBK");
+        assert_eq!(OutputCharset::parse("iso-8859-1").unwrap().name(), "windows-1252");
+        assert_eq!(OutputCharset::parse("Shift_JIS").unwrap().name(), "Shift_JIS");
+        assert!(OutputCharset::parse("utf-16le").is_err());
+        assert!(OutputCharset::parse("klingon").is_err());
+    }
+}
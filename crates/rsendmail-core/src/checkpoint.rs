@@ -0,0 +1,126 @@
+//! 断点续发的检查点文件
+//!
+//! 设置 `checkpoint_file` 后，EML 批量发送每成功发送一封邮件就向检查点文件追加一行
+//! 该文件的路径（与扫描目录时得到的路径字符串一致）。再次运行时扫描结果中已出现在
+//! 检查点里的文件会被跳过，进程崩溃或被中止后重新运行即可从中断处继续。
+//!
+//! 各进程组共享同一个 [`Checkpoint`]，写入经互斥锁串行化，行与行之间不会交错。
+//! 写入带缓冲，每 [`FLUSH_EVERY`] 条以及发送结束时刷新到磁盘；进程崩溃时最多丢失
+//! 最后一批未刷新的记录，这些文件会在下次运行时被重新发送。
+
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::sync::Mutex;
+
+use log::warn;
+
+use crate::progress::ProgressObserver;
+
+/// 累积多少条记录后刷新一次
+pub const FLUSH_EVERY: usize = 100;
+
+struct Writer {
+    file: BufWriter<File>,
+    pending: usize,
+}
+
+pub struct Checkpoint {
+    writer: Mutex<Writer>,
+}
+
+impl Checkpoint {
+    /// 以追加方式打开检查点文件，不存在时创建
+    pub fn open(path: &str) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            writer: Mutex::new(Writer {
+                file: BufWriter::new(file),
+                pending: 0,
+            }),
+        })
+    }
+
+    /// 读取检查点中已记录的文件路径，文件不存在时返回空集合
+    pub fn load(path: &str) -> io::Result<HashSet<String>> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(content
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(HashSet::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 清空检查点文件（文件不存在时不做任何处理）
+    pub fn reset(path: &str) -> io::Result<()> {
+        match OpenOptions::new().write(true).truncate(true).open(path) {
+            Ok(_) => Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 记录一个已成功发送的文件
+    pub fn record(&self, file_path: &str) -> io::Result<()> {
+        // 路径中的换行会破坏按行存储的格式，这类文件不记录（下次运行会重新发送）
+        if file_path.contains(['\r', '\n']) {
+            return Ok(());
+        }
+        let mut writer = self.writer.lock().unwrap();
+        writeln!(writer.file, "{}", file_path)?;
+        writer.pending += 1;
+        if writer.pending >= FLUSH_EVERY {
+            writer.pending = 0;
+            writer.file.flush()?;
+        }
+        Ok(())
+    }
+
+    /// 将缓冲的记录写入磁盘
+    pub fn flush(&self) -> io::Result<()> {
+        let mut writer = self.writer.lock().unwrap();
+        writer.pending = 0;
+        writer.file.flush()
+    }
+}
+
+/// 把发送成功的文件记入检查点
+impl ProgressObserver for Checkpoint {
+    fn email_sent(&self, path: &str) {
+        if let Err(e) = self.record(path) {
+            warn!("写入检查点失败: {}: {}", path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_are_flushed_and_reloaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checkpoint.txt");
+        let path = path.to_str().unwrap();
+
+        let checkpoint = Checkpoint::open(path).unwrap();
+        checkpoint.record("/mail/a.eml").unwrap();
+        checkpoint.record("/mail/b\n.eml").unwrap();
+        // 未达到刷新阈值前记录仍在缓冲区中
+        assert!(Checkpoint::load(path).unwrap().is_empty());
+        checkpoint.flush().unwrap();
+        assert_eq!(
+            Checkpoint::load(path).unwrap(),
+            HashSet::from(["/mail/a.eml".to_string()])
+        );
+
+        Checkpoint::reset(path).unwrap();
+        assert!(Checkpoint::load(path).unwrap().is_empty());
+        assert!(Checkpoint::load(dir.path().join("missing").to_str().unwrap())
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,108 @@
+//! 按失败率提前终止发送的熔断器
+//!
+//! 配置了 `abort_error_rate` 时，熔断器经进度回调统计所有进程组已尝试与失败的邮件数。
+//! 尝试数达到 `abort_min_samples` 后，失败率一旦超过阈值就将共享的运行状态切换为
+//! 排空：正在发送的邮件照常完成，但不再开始新的邮件，本轮发送以错误结束。
+//! 用于凭证错误、IP被列入黑名单等情况下避免把整批邮件都发送失败。
+
+use log::error;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use crate::config::Config;
+use crate::progress::ProgressObserver;
+use crate::shutdown::RunControl;
+
+/// 所有进程组共享的失败率统计
+pub struct CircuitBreaker {
+    threshold: f64,
+    min_samples: usize,
+    attempted: AtomicUsize,
+    failed: AtomicUsize,
+    tripped: AtomicBool,
+    running: RunControl,
+}
+
+impl CircuitBreaker {
+    /// 根据 `abort_error_rate` 与 `abort_min_samples` 创建，未配置阈值时返回 None
+    pub fn from_config(config: &Config, running: RunControl) -> Option<Self> {
+        config.abort_error_rate.map(|threshold| Self {
+            threshold,
+            min_samples: config.abort_min_samples.max(1),
+            attempted: AtomicUsize::new(0),
+            failed: AtomicUsize::new(0),
+            tripped: AtomicBool::new(false),
+            running,
+        })
+    }
+
+    /// 记录一封邮件的发送结果，失败率超过阈值时停止开始新的邮件
+    pub fn record(&self, failed: bool) {
+        let attempted = self.attempted.fetch_add(1, Ordering::SeqCst) + 1;
+        let failures = if failed {
+            self.failed.fetch_add(1, Ordering::SeqCst) + 1
+        } else {
+            self.failed.load(Ordering::SeqCst)
+        };
+        if attempted < self.min_samples || failures as f64 / attempted as f64 <= self.threshold {
+            return;
+        }
+        if !self.tripped.swap(true, Ordering::SeqCst) {
+            error!(
+                "失败率 {:.1}% 超过熔断阈值 {:.1}%（{}/{}），停止发送",
+                failures as f64 * 100.0 / attempted as f64,
+                self.threshold * 100.0,
+                failures,
+                attempted
+            );
+            self.running.drain();
+        }
+    }
+
+    /// 熔断器是否已触发
+    pub fn tripped(&self) -> bool {
+        self.tripped.load(Ordering::SeqCst)
+    }
+
+    /// (失败数, 已尝试数)
+    pub fn counts(&self) -> (usize, usize) {
+        (self.failed.load(Ordering::SeqCst), self.attempted.load(Ordering::SeqCst))
+    }
+}
+
+/// 将每封邮件的结果计入熔断器
+impl ProgressObserver for CircuitBreaker {
+    fn email_sent(&self, _path: &str) {
+        self.record(false);
+    }
+
+    fn email_failed(&self, _path: &str, _error: &str) {
+        self.record(true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trips_only_after_min_samples() {
+        let config = Config {
+            abort_error_rate: Some(0.5),
+            abort_min_samples: 4,
+            ..Config::default()
+        };
+        let running = RunControl::new();
+        let breaker = CircuitBreaker::from_config(&config, running.clone()).unwrap();
+
+        for _ in 0..3 {
+            breaker.record(true);
+        }
+        assert!(!breaker.tripped());
+        assert!(running.is_running());
+
+        breaker.record(false);
+        assert!(breaker.tripped());
+        assert!(!running.is_running());
+        assert_eq!(breaker.counts(), (3, 4));
+    }
+}
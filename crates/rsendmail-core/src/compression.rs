@@ -0,0 +1,89 @@
+//! gzip 压缩邮件文件的透明读取
+//!
+//! 开启 `decompress` 后，EML 扫描同时匹配 `<扩展名>.gz` 文件，读取时按魔数（`1f 8b`）
+//! 而不是文件名判断是否为 gzip 内容，因此未压缩的文件照常处理。解压在内存中完成，
+//! 附件模式下压缩的附件也会先完整解压，不再按块从磁盘流式读取。
+
+use flate2::read::MultiGzDecoder;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// gzip 文件头的魔数
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// 内容是否以 gzip 魔数开头
+pub fn is_gzip(data: &[u8]) -> bool {
+    data.starts_with(&GZIP_MAGIC)
+}
+
+/// 读取文件开头判断是否为 gzip 内容，读取后回到文件开头
+pub fn is_gzip_file<R: Read + Seek>(file: &mut R) -> io::Result<bool> {
+    let mut magic = [0u8; 2];
+    let gzip = file.read_exact(&mut magic).is_ok() && magic == GZIP_MAGIC;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(gzip)
+}
+
+/// gzip 内容解压后返回，其余内容原样返回；支持多成员（拼接）的 gzip 文件
+pub fn decompress_if_gzip(data: Vec<u8>) -> io::Result<Vec<u8>> {
+    if !is_gzip(&data) {
+        return Ok(data);
+    }
+    gunzip(data.as_slice())
+}
+
+/// 解压整个 gzip 流
+pub fn gunzip<R: Read>(reader: R) -> io::Result<Vec<u8>> {
+    let mut decompressed = Vec::new();
+    MultiGzDecoder::new(reader).read_to_end(&mut decompressed)?;
+    Ok(decompressed)
+}
+
+/// 文件名是否带有 `.<extension>.gz` 后缀（不区分 `.gz` 的大小写）
+pub fn has_gz_extension(path: &Path, extension: &str) -> bool {
+    let Some(name) = path.file_name().map(|name| name.to_string_lossy()) else {
+        return false;
+    };
+    let stem = strip_gz_suffix(&name);
+    stem.len() < name.len()
+        && Path::new(stem)
+            .extension()
+            .is_some_and(|ext| ext.to_string_lossy() == extension)
+}
+
+/// 去掉文件名末尾的 `.gz`，没有该后缀时原样返回
+pub fn strip_gz_suffix(name: &str) -> &str {
+    match name.len().checked_sub(3) {
+        Some(pos) if name.is_char_boundary(pos) && name[pos..].eq_ignore_ascii_case(".gz") => {
+            &name[..pos]
+        }
+        _ => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    #[test]
+    fn test_gzip_detection_and_names() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"Subject: hi\r\n\r\nbody\r\n").unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        assert!(is_gzip(&compressed));
+        assert_eq!(decompress_if_gzip(compressed).unwrap(), b"Subject: hi\r\n\r\nbody\r\n");
+        assert_eq!(decompress_if_gzip(b"plain".to_vec()).unwrap(), b"plain");
+        assert!(decompress_if_gzip(vec![0x1f, 0x8b, 0]).is_err());
+
+        assert!(has_gz_extension(Path::new("/mail/a.eml.gz"), "eml"));
+        assert!(has_gz_extension(Path::new("/mail/a.eml.GZ"), "eml"));
+        assert!(!has_gz_extension(Path::new("/mail/a.eml"), "eml"));
+        assert!(!has_gz_extension(Path::new("/mail/a.txt.gz"), "eml"));
+        assert_eq!(strip_gz_suffix("report.pdf.gz"), "report.pdf");
+        assert_eq!(strip_gz_suffix("report.pdf"), "report.pdf");
+    }
+}
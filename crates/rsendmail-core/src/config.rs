@@ -1,16 +1,33 @@
 use log::LevelFilter;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::backoff;
 
 /// 邮件发送配置（无 CLI 依赖）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
-    /// SMTP服务器地址
+    /// SMTP服务器地址，为绝对路径时通过Unix域套接字连接（仅限明文会话）
+    #[serde(default)]
     pub smtp_server: String,
 
     /// SMTP服务器端口
     #[serde(default = "default_port")]
     pub port: u16,
 
+    /// 投递协议：`smtp`（默认）或 `lmtp`。LMTP 以 LHLO 代替 EHLO，DATA 结束后服务器
+    /// 对每个已接受的收件人各返回一行应答；仅支持明文连接的EML批量发送
+    #[serde(default = "default_protocol")]
+    pub protocol: String,
+
+    /// EHLO/HELO（LMTP 为 LHLO）中宣告的主机名，未设置时使用本机主机名
+    #[serde(default)]
+    pub ehlo_name: Option<String>,
+
     /// 发件人邮箱地址（EML模式下可选，将从EML文件的From头提取）
     #[serde(default)]
     pub from: Option<String>,
@@ -19,49 +36,238 @@ pub struct Config {
     #[serde(default)]
     pub to: Option<String>,
 
+    /// 密送收件人 (多个地址请用逗号分隔)，只作为额外的 RCPT TO 收件人，不写入任何邮件头
+    #[serde(default)]
+    pub bcc: Option<String>,
+
+    /// Reply-To 邮件头（回复地址，可带显示名），只写入邮件头，不影响SMTP信封；
+    /// 原样转发时替换原邮件中的 Reply-To
+    #[serde(default)]
+    pub reply_to: Option<String>,
+
+    /// Sender 邮件头（代发时的实际发送者），只写入邮件头，不影响SMTP信封；
+    /// 原样转发时替换原邮件中的 Sender
+    #[serde(default)]
+    pub sender_header: Option<String>,
+
     /// 在EML模式下，是否将Cc/Bcc头中的地址也作为SMTP RCPT TO收件人
     #[serde(default)]
     pub envelope_cc_bcc: bool,
 
-    /// 邮件文件所在目录
+    /// 收件人域名允许列表：设置后只向这些域名（含子域名）的收件人投递，其余收件人被丢弃；
+    /// 只作用于EML发送，附件模式下拒绝
+    #[serde(default)]
+    pub recipient_allow_domains: Option<Vec<String>>,
+
+    /// 收件人域名阻止列表：这些域名（含子域名）的收件人被丢弃，优先于允许列表；
+    /// 与允许列表相同，附件模式下拒绝
+    #[serde(default)]
+    pub recipient_block_domains: Option<Vec<String>>,
+
+    /// 在EML模式下，是否为每个收件人单独执行 MAIL FROM/RCPT TO/DATA 事务，
+    /// 以便按收件人统计投递结果
+    #[serde(default)]
+    pub separate_envelopes: bool,
+
+    /// 在EML模式下，是否按收件人域名分组：同一域名的收件人共用一次事务，
+    /// 不同域名分别执行事务。`separate_envelopes` 优先
+    #[serde(default)]
+    pub group_by_domain: bool,
+
+    /// 在 `separate_envelopes` 模式下使用VERP：将收件人编码进 MAIL FROM
+    /// （如 `bounces+user=example.com@mydomain`），以便从退信识别收件人
+    #[serde(default)]
+    pub verp: bool,
+
+    /// VERP使用的基础退信地址，未设置时使用信封发件人
+    #[serde(default)]
+    pub verp_base: Option<String>,
+
+    /// 在EML模式下，是否优先使用每个EML文件旁的 `<文件名>.to` 收件人文件
+    /// （逗号分隔），文件不存在时回退到 `to` 或EML中的收件人
+    #[serde(default)]
+    pub recipient_from_sidecar: bool,
+
+    /// 邮件文件所在目录（多个目录请用逗号分隔）
     pub dir: Option<String>,
 
+    /// 单个EML文件路径：只发送这一封邮件（使用一个连接），与 `dir`、`attachment`、
+    /// `attachment_dir` 互斥
+    #[serde(default)]
+    pub eml_file: Option<String>,
+
+    /// JSON Lines 输入（文件路径，`-` 表示标准输入）：每行一个包含 `to`、`subject`、`body`
+    /// 与可选 `attachments` 的对象，逐行构建邮件后按EML批量发送
+    #[serde(default)]
+    pub jsonl_input: Option<String>,
+
     /// 邮件文件扩展名
     #[serde(default = "default_extension")]
     pub extension: String,
 
+    /// 同时匹配 `<extension>.gz` 文件，读取时透明解压 gzip 内容（按魔数识别）；
+    /// 附件模式下发送解压后的附件
+    #[serde(default)]
+    pub decompress: bool,
+
+    /// 按内容去重：同一次发送中内容（解压、匿名化之后）与已排队文件完全相同的文件被跳过
+    #[serde(default)]
+    pub dedup: bool,
+
+    /// 在发出的邮件中写入 `X-RSendMail-Dedup` 邮件头，值为源文件内容（解压、匿名化之后）的
+    /// SHA-256，供接收方在重新运行后识别重复邮件
+    #[serde(default)]
+    pub dedup_header: bool,
+
+    /// 严格解析：第一封无法解析的EML即终止本轮发送并报告其路径，默认只计数并跳过
+    #[serde(default)]
+    pub strict_parse: bool,
+
+    /// 快速失败：第一封发送失败的邮件即终止本轮发送并返回错误，默认只计数并继续发送
+    #[serde(default)]
+    pub fail_fast: bool,
+
+    /// 只发送文件名匹配该glob模式的文件（例如 `campaign-*.eml`），设置后取代 `extension` 过滤
+    #[serde(default)]
+    pub include_glob: Option<String>,
+
+    /// 排除文件名匹配该glob模式的文件，优先于 `include_glob`
+    #[serde(default)]
+    pub exclude_glob: Option<String>,
+
+    /// 只发送修改时间晚于该时刻的文件，格式为 RFC3339（如 `2024-05-01T08:00:00+08:00`）
+    /// 或 Unix 时间戳（秒）
+    #[serde(default)]
+    pub modified_after: Option<String>,
+
     /// 进程数，auto表示自动设置为CPU核心数，或者指定具体数字
     #[serde(default = "default_processes")]
     pub processes: String,
 
+    /// 所有进程组合计同时打开的SMTP连接上限，未设置时每个进程组各持有一个连接；
+    /// 进程组在建立连接前等待空闲名额，连接关闭后归还
+    #[serde(default)]
+    pub max_connections: Option<usize>,
+
+    /// 每个SMTP端点（`host:port`，配置了 `relays` 时即每个中继）同时打开的连接上限，
+    /// 与 `max_connections` 同时生效
+    #[serde(default)]
+    pub per_host_connections: Option<usize>,
+
     /// 每个SMTP会话连续发送的邮件数量
     #[serde(default = "default_batch_size")]
     pub batch_size: usize,
 
-    /// SMTP会话超时时间（秒）
+    /// 每个SMTP会话累计发送的DATA字节数上限：达到后即使未满 `batch_size` 也关闭连接，
+    /// 批次中剩余的邮件在新连接上继续发送
+    #[serde(default)]
+    pub batch_max_bytes: Option<u64>,
+
+    /// 自适应批次：以 `batch_size` 为起点，批次成功时加一、遇到421或临时性失败时减半；
+    /// 连接在批次之间照常复用
+    #[serde(default)]
+    pub adaptive_batch: bool,
+
+    /// 自适应批次的下限
+    #[serde(default = "default_adaptive_batch_min")]
+    pub adaptive_batch_min: usize,
+
+    /// 自适应批次的上限
+    #[serde(default = "default_adaptive_batch_max")]
+    pub adaptive_batch_max: usize,
+
+    /// SMTP会话超时时间（秒），用于每条SMTP命令；未单独设置时也用于建立连接和发送邮件内容
     #[serde(default = "default_smtp_timeout")]
     pub smtp_timeout: u64,
 
+    /// 建立连接（TCP连接、问候语、TLS握手与认证）的超时时间（秒），未设置时使用 `smtp_timeout`，
+    /// 设置较小的值可在服务器无响应时尽快切换到其他中继
+    #[serde(default)]
+    pub connect_timeout: Option<u64>,
+
+    /// 发送邮件内容（DATA/BDAT）的超时时间（秒），未设置时使用 `smtp_timeout`，
+    /// 大邮件的传输可能明显长于普通命令
+    #[serde(default)]
+    pub data_timeout: Option<u64>,
+
+    /// 单次发送（一轮）的总时长上限（秒）：到期后进入排空状态，正在发送的邮件照常完成，
+    /// 不再开始新的邮件，返回已完成部分的统计
+    #[serde(default)]
+    pub max_run_seconds: Option<u64>,
+
     /// 日志级别 (error/warn/info/debug/trace)
     #[serde(default = "default_log_level")]
     pub log_level: String,
 
+    /// 日志输出格式：text 为可读文本，json 为每行一个JSON对象
+    #[serde(default = "default_log_format")]
+    pub log_format: String,
+
+    /// 安静模式：逐封邮件的处理与发送成功日志降为debug级别，错误、轮次汇总和最终统计不受影响，
+    /// 便于在info级别下运行大批量发送
+    #[serde(default)]
+    pub quiet: bool,
+
+    /// 控制台日志颜色：auto（仅输出到终端时着色）、always 或 never，未设置时按 auto 处理
+    #[serde(default)]
+    pub color: Option<String>,
+
+    /// 发送EML文件时在终端显示进度条（仅CLI使用）
+    #[serde(default)]
+    pub progress: bool,
+
     /// 是否保留原始邮件头
     #[serde(default)]
     pub keep_headers: bool,
 
+    /// 在EML模式下（`modify_headers` 除外），以每封邮件的 Return-Path（其次 From）邮件头
+    /// 作为信封发件人 MAIL FROM，邮件头中没有地址时回退到 `from`
+    #[serde(default)]
+    pub envelope_from_header: bool,
+
     /// 是否匿名化邮箱地址
     #[serde(default)]
     pub anonymize_emails: bool,
 
+    /// 是否匿名化邮件内容中的电话号码
+    #[serde(default)]
+    pub anonymize_phones: bool,
+
+    /// 是否匿名化邮件内容中的IPv4地址
+    #[serde(default)]
+    pub anonymize_ips: bool,
+
     /// 邮箱匿名化域名（例如：example.com），匿名化后的邮箱将变为随机字符@domain
     #[serde(default = "default_anonymize_domain")]
     pub anonymize_domain: String,
 
+    /// 邮箱匿名化密钥：设置后邮箱用户名由原地址的 HMAC-SHA256（以此为密钥）截断生成，
+    /// 同一地址在不同运行之间映射到相同的假名且不可逆推；未设置时随机生成
+    #[serde(default)]
+    pub anonymize_key: Option<String>,
+
     /// 是否使用--from和--to参数修改邮件头中的From和To
     #[serde(default)]
     pub modify_headers: bool,
 
+    /// 在modify_headers重建邮件时生成新的 Message-ID（使用 anonymize_domain 作为域名），
+    /// 默认保留原始 Message-ID
+    #[serde(default)]
+    pub regenerate_message_id: bool,
+
+    /// 在modify_headers重建邮件时保留原始 Date 头（默认开启）；关闭或原邮件没有 Date 时使用当前时间
+    #[serde(default = "default_preserve_date")]
+    pub preserve_date: bool,
+
+    /// 在modify_headers重建仅含HTML正文的邮件时，附加由HTML去除标签生成的纯文本正文
+    #[serde(default)]
+    pub generate_text_fallback: bool,
+
+    /// 在modify_headers重建邮件时正文（text/plain 与 text/html）使用的字符集，如 gb2312；
+    /// 未设置时使用 UTF-8
+    #[serde(default)]
+    pub output_charset: Option<String>,
+
     /// 是否无限循环发送（直到用户中断）
     #[serde(default, rename = "loop")]
     pub r#loop: bool,
@@ -74,16 +280,59 @@ pub struct Config {
     #[serde(default = "default_loop_interval")]
     pub loop_interval: u64,
 
+    /// 每天允许发送的本地时间段，如 `08:00-18:00`（可跨越午夜），不在时段内时每轮开始前等待
+    #[serde(default)]
+    pub send_window: Option<String>,
+
     /// 发送失败后重试的间隔时间（秒）
     #[serde(default = "default_retry_interval")]
     pub retry_interval: u64,
 
+    /// 连续失败时是否按指数退避延长重试间隔（retry_interval, 2x, 4x, …，带±20%随机抖动）
+    #[serde(default)]
+    pub retry_backoff: bool,
+
+    /// 指数退避时重试间隔的上限（秒）
+    #[serde(default = "default_retry_max_interval")]
+    pub retry_max_interval: u64,
+
+    /// 单封邮件遇到临时性失败（4xx）时在同一连接上重试的次数，为0时不重试；
+    /// 永久性失败（5xx）与连接错误不重试
+    #[serde(default)]
+    pub message_retries: u32,
+
+    /// 单封邮件重试前等待的时间（毫秒）
+    #[serde(default = "default_message_retry_delay_ms")]
+    pub message_retry_delay_ms: u64,
+
+    /// 主发送结束后，将临时性失败（4xx）的文件集中再发送一轮；
+    /// 重试轮次中仍失败的文件才计入失败统计，并在此时才触发失败回调、钩子与失败邮件保存
+    #[serde(default)]
+    pub retry_round: bool,
+
+    /// 失败率熔断阈值（0~1）：已尝试的邮件数达到 `abort_min_samples` 后，
+    /// 失败率超过该值时所有进程组停止发送，本轮以错误结束
+    #[serde(default)]
+    pub abort_error_rate: Option<f64>,
+
+    /// 开始按 `abort_error_rate` 判断前至少需要尝试的邮件数
+    #[serde(default = "default_abort_min_samples")]
+    pub abort_min_samples: usize,
+
     /// 附件文件路径，用于发送普通文件作为附件
     pub attachment: Option<String>,
 
+    /// 多个附件文件路径，与 `attachment` 一起作为同一封邮件的附件发送
+    #[serde(default)]
+    pub attachments: Vec<String>,
+
     /// 附件目录路径，发送目录下所有文件为单独的邮件
     pub attachment_dir: Option<String>,
 
+    /// 附件大小上限（字节），超过的文件不读取、不发送，计为"附件过大"错误
+    #[serde(default)]
+    pub max_attachment_bytes: Option<u64>,
+
     /// 主题模板，支持变量 {filename}
     pub subject_template: Option<String>,
 
@@ -93,10 +342,35 @@ pub struct Config {
     /// HTML内容模板，支持变量 {filename}
     pub html_template: Option<String>,
 
+    /// HTML正文引用的内嵌图片，每项为 `cid:路径`，HTML中以 `cid:<cid>` 引用；
+    /// 只在设置了 `html_template` 时加入邮件
+    #[serde(default)]
+    pub inline_images: Vec<String>,
+
     /// 批次内每封邮件发送间隔（毫秒）
     #[serde(default)]
     pub email_send_interval_ms: u64,
 
+    /// 发送间隔的随机抖动（毫秒）：每次等待的时长在 `email_send_interval_ms` 上下
+    /// 各浮动该值的范围内均匀抽取（不小于0），为0时为固定间隔；
+    /// 未设置 `email_send_interval_ms` 时同样生效
+    #[serde(default)]
+    pub interval_jitter_ms: u64,
+
+    /// 所有进程组合计的每秒最大发送邮件数（令牌桶限速），与 `email_send_interval_ms` 互斥，
+    /// 同时设置时以速率上限为准
+    #[serde(default)]
+    pub max_rate_per_second: Option<f64>,
+
+    /// 预热时长（秒）：从第一封邮件开始，速率在此期间从 `warmup_start_rate` 线性增加到
+    /// `max_rate_per_second`，为0时不预热
+    #[serde(default)]
+    pub warmup_seconds: u64,
+
+    /// 预热开始时的每秒发送邮件数
+    #[serde(default = "default_warmup_start_rate")]
+    pub warmup_start_rate: f64,
+
     /// 是否使用邮箱账号登录模式（通过用户名和密码验证发送邮件）
     #[serde(default)]
     pub auth_mode: bool,
@@ -104,22 +378,191 @@ pub struct Config {
     /// 邮箱账号用户名（仅在auth_mode=true时需要）
     pub username: Option<String>,
 
-    /// 邮箱账号密码（仅在auth_mode=true时需要）
+    /// 邮箱账号密码（仅在auth_mode=true时需要）；未设置时依次尝试 `password_file`
+    /// 和环境变量 `RSENDMAIL_SMTP_PASSWORD`，见 [`Config::resolve_password`]
     pub password: Option<String>,
 
+    /// 密码文件路径，读取文件第一行作为密码，避免密码出现在命令行和进程列表中
+    #[serde(default)]
+    pub password_file: Option<String>,
+
+    /// OAuth2 访问令牌：设置后以 XOAUTH2 机制（`username` + 令牌）认证，不再需要密码
+    #[serde(default)]
+    pub oauth2_token: Option<String>,
+
+    /// 指定认证机制（plain、login、cram-md5 或 xoauth2，不区分大小写）；未设置时从服务器
+    /// 通告的机制中自动选择，设置了 `oauth2_token` 时为 xoauth2
+    #[serde(default)]
+    pub auth_mechanism: Option<String>,
+
     /// 使用TLS加密连接 (为了兼容大多数SMTP服务器，当端口是465时将自动启用)
     #[serde(default)]
     pub use_tls: bool,
 
+    /// 要求使用STARTTLS加密连接：端口非465时即使未开启 `use_tls` 也通过STARTTLS升级，
+    /// 服务器EHLO应答中没有STARTTLS时放弃发送并记录错误，而不是回退为明文。
+    /// 端口465使用隐式TLS，本身已加密，不受此选项影响。`auth_mode` 本身拒绝非TLS连接，
+    /// 开启此选项后认证模式无需再指定 `use_tls`，且只会在STARTTLS成功后发送凭证
+    #[serde(default)]
+    pub require_starttls: bool,
+
     /// 是否接受无效的证书
     #[serde(default)]
     pub accept_invalid_certs: bool,
 
+    /// 固定服务器证书的SHA-256指纹（十六进制，可带冒号）：设置后只接受指纹一致的证书，
+    /// 不再按根证书校验，指纹不一致时即使开启 `accept_invalid_certs` 也拒绝连接
+    #[serde(default)]
+    pub pinned_cert_sha256: Option<String>,
+
+    /// TLS握手失败时在同一端口改用另一种方式重试：端口465的隐式TLS失败时改用STARTTLS，
+    /// 其余端口STARTTLS失败时改用隐式TLS。默认关闭，避免掩盖真实的配置错误
+    #[serde(default)]
+    pub tls_fallback: bool,
+
+    /// 出站SMTP连接使用的代理，支持 socks5://[user:pass@]host:port 与 http://host:port
+    #[serde(default)]
+    pub proxy: Option<String>,
+
+    /// EML批量发送使用的SMTP中继列表（`host[:port][*weight]`），设置后替代
+    /// `smtp_server`/`port`，按权重轮询并在连接失败或收到421时暂时跳过该中继
+    #[serde(default)]
+    pub relays: Vec<String>,
+
+    /// 中继被标记为不可用后跳过的时长（秒）
+    #[serde(default = "default_relay_cooldown_secs")]
+    pub relay_cooldown_secs: u64,
+
+    /// 未设置 `smtp_server` 时不经中继，按收件人域名查询MX记录并直接投递到
+    /// 优先级最高且可连接的MX主机（端口为 `port`，默认25）
+    #[serde(default)]
+    pub direct_mx: bool,
+
     /// 发送失败的EML文件保存目录
     pub failed_emails_dir: Option<String>,
 
+    /// 永久性失败（5xx）的EML文件移动（而非复制）到 `failed_emails_dir`
+    #[serde(default)]
+    pub move_hard_bounces: bool,
+
+    /// 发送成功的EML文件保存目录，重新运行时这些文件已不在输入目录中
+    #[serde(default)]
+    pub sent_emails_dir: Option<String>,
+
+    /// 发送成功的EML文件移动（而非复制）到 `sent_emails_dir`
+    #[serde(default)]
+    pub move_on_success: bool,
+
     /// 日志文件保存路径（如果指定，日志会同时输出到控制台和文件）
     pub log_file: Option<String>,
+
+    /// 全部轮次结束后写入JSON格式统计摘要的路径（"-" 表示输出到标准输出）
+    #[serde(default)]
+    pub stats_json: Option<String>,
+
+    /// 全部轮次结束后将失败文件导出为CSV的路径（列：file_path,error_type,timestamp）
+    #[serde(default)]
+    pub failed_csv: Option<String>,
+
+    /// 全部轮次结束后将统计摘要发送到该邮箱，使用与发送任务相同的SMTP配置
+    #[serde(default)]
+    pub report_to: Option<String>,
+
+    /// 断点续发的检查点文件：记录发送成功的EML路径，再次运行时跳过其中的文件
+    #[serde(default)]
+    pub checkpoint_file: Option<String>,
+
+    /// 开始发送前清空 `checkpoint_file`
+    #[serde(default)]
+    pub reset_checkpoint: bool,
+
+    /// 投递清单文件：EML批量发送时每次投递尝试追加一行（源文件、信封发件人、收件人、
+    /// 结果与服务器应答），与只记录成功文件的检查点不同，用于审计
+    #[serde(default)]
+    pub manifest_file: Option<String>,
+
+    /// EML批量发送中每封邮件发送成功后运行的命令，`{file}` 替换为邮件文件路径
+    #[serde(default)]
+    pub on_success_command: Option<String>,
+
+    /// EML批量发送中每封邮件发送失败后运行的命令，`{file}` 替换为邮件文件路径
+    #[serde(default)]
+    pub on_failure_command: Option<String>,
+
+    /// 服务器通告 PIPELINING 时连续发送 MAIL FROM 与全部 RCPT TO 后再统一读取应答
+    #[serde(default)]
+    pub pipelining: bool,
+
+    /// 服务器通告 CHUNKING 时改用BDAT分块发送邮件内容，未通告时仍使用DATA
+    #[serde(default)]
+    pub use_bdat: bool,
+
+    /// BDAT每块的字节数
+    #[serde(default = "default_bdat_chunk_size")]
+    pub bdat_chunk_size: usize,
+
+    /// DKIM签名使用的RSA私钥（PEM文件路径），与 `dkim_selector`、`dkim_domain` 同时设置时生效
+    #[serde(default)]
+    pub dkim_private_key: Option<String>,
+
+    /// DKIM选择器（s= 标签）
+    #[serde(default)]
+    pub dkim_selector: Option<String>,
+
+    /// DKIM签名域名（d= 标签）
+    #[serde(default)]
+    pub dkim_domain: Option<String>,
+
+    /// 在分配给各进程组之前随机打乱EML文件顺序
+    #[serde(default)]
+    pub shuffle: bool,
+
+    /// 打乱顺序使用的随机种子，设置后每次运行得到相同的顺序
+    #[serde(default)]
+    pub shuffle_seed: Option<u64>,
+
+    /// 本次运行最多发送的EML文件数（在扩展名、glob 与检查点过滤之后截断），所有进程组合计
+    #[serde(default)]
+    pub limit: Option<usize>,
+
+    /// `dir` 中没有匹配的邮件文件时仍视为发送成功；默认 `check_input_files` 发出警告并以
+    /// [`NoInputFiles`] 错误结束，避免路径或扩展名写错时被当作“全部发送成功”。
+    /// 修改时间与检查点过滤后为空不受影响
+    ///
+    /// [`NoInputFiles`]: crate::mailer::NoInputFiles
+    #[serde(default)]
+    pub allow_empty: bool,
+
+    /// 演练模式：只读取、解析并构建每封邮件，不建立任何SMTP连接
+    #[serde(default)]
+    pub dry_run: bool,
+
+    /// 只检查EML文件（能否解析、头部结构、编码、发件人与收件人），输出问题列表后退出，
+    /// 不建立任何SMTP连接；`send_all_with_cancel` 在有问题的文件时返回错误
+    #[serde(default)]
+    pub validate_only: bool,
+
+    /// 只统计按当前过滤条件将要发送的EML文件数量与总字节数后退出，不建立任何SMTP连接；
+    /// `send_all_with_cancel` 此时只记录统计结果，返回空的 `Stats`
+    #[serde(default)]
+    pub count_only: bool,
+
+    /// 构建单个EML文件（文件路径，或 `first` 表示第一个匹配的文件）将要发送的DATA内容，
+    /// 输出后退出，不建立任何SMTP连接；经过与发送相同的匿名化、邮件头改写与DKIM签名
+    #[serde(default)]
+    pub print_message: Option<String>,
+
+    /// `print_message` 的输出文件，未设置时写到标准输出
+    #[serde(default)]
+    pub print_message_output: Option<String>,
+
+    /// 发送前输出预估摘要（文件数、总大小、收件人数、预计耗时）并等待确认
+    #[serde(default)]
+    pub confirm: bool,
+
+    /// 附加到每封外发邮件的邮件头 (名称, 值)，值中的 `{filename}` 替换为当前文件名
+    #[serde(default)]
+    pub extra_headers: Vec<(String, String)>,
 }
 
 // 默认值函数
@@ -139,14 +582,38 @@ fn default_batch_size() -> usize {
     1
 }
 
+fn default_adaptive_batch_min() -> usize {
+    1
+}
+
+fn default_adaptive_batch_max() -> usize {
+    100
+}
+
 fn default_smtp_timeout() -> u64 {
     30
 }
 
+fn default_relay_cooldown_secs() -> u64 {
+    60
+}
+
+fn default_warmup_start_rate() -> f64 {
+    1.0
+}
+
 fn default_log_level() -> String {
     "info".to_string()
 }
 
+fn default_protocol() -> String {
+    "smtp".to_string()
+}
+
+fn default_log_format() -> String {
+    "text".to_string()
+}
+
 fn default_anonymize_domain() -> String {
     "example.com".to_string()
 }
@@ -159,10 +626,33 @@ fn default_loop_interval() -> u64 {
     1
 }
 
+fn default_preserve_date() -> bool {
+    true
+}
+
 fn default_retry_interval() -> u64 {
     5
 }
 
+fn default_abort_min_samples() -> usize {
+    100
+}
+
+fn default_bdat_chunk_size() -> usize {
+    1024 * 1024
+}
+
+fn default_message_retry_delay_ms() -> u64 {
+    1000
+}
+
+fn default_retry_max_interval() -> u64 {
+    300
+}
+
+/// 未通过参数或文件提供密码时读取的环境变量
+pub const PASSWORD_ENV: &str = "RSENDMAIL_SMTP_PASSWORD";
+
 #[derive(Debug, PartialEq)]
 pub enum ProcessMode {
     Auto,
@@ -170,6 +660,20 @@ pub enum ProcessMode {
 }
 
 impl Config {
+    /// 从配置文件加载：扩展名为 `.toml` 时按TOML解析，否则按JSON解析（与GUI保存的格式一致）。
+    /// 文件中未出现的字段取默认值
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let is_toml = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+        if is_toml {
+            Ok(toml::from_str(&content)?)
+        } else {
+            Ok(serde_json::from_str(&content)?)
+        }
+    }
+
     pub fn get_log_level(&self) -> LevelFilter {
         match self.log_level.to_lowercase().as_str() {
             "error" => LevelFilter::Error,
@@ -181,6 +685,105 @@ impl Config {
         }
     }
 
+    /// 第 `consecutive_failures` 次连续失败后（从0开始）下一轮前的等待时间
+    pub fn retry_delay(&self, consecutive_failures: u32) -> Duration {
+        if self.retry_backoff {
+            backoff::with_jitter(backoff::next_backoff(
+                consecutive_failures,
+                self.retry_interval,
+                self.retry_max_interval,
+            ))
+        } else {
+            Duration::from_secs(self.retry_interval)
+        }
+    }
+
+    /// 按 `password` → `password_file` 第一行 → 环境变量 `RSENDMAIL_SMTP_PASSWORD`
+    /// 的顺序解析密码，均未提供时返回 None
+    pub fn resolve_password(&self) -> io::Result<Option<String>> {
+        self.resolve_password_with(|name| std::env::var(name).ok())
+    }
+
+    fn resolve_password_with(&self, env: impl Fn(&str) -> Option<String>) -> io::Result<Option<String>> {
+        if let Some(password) = &self.password {
+            return Ok(Some(password.clone()));
+        }
+        if let Some(path) = &self.password_file {
+            let content = fs::read_to_string(path)?;
+            let first_line = content.lines().next().unwrap_or_default();
+            return Ok(Some(first_line.to_string()));
+        }
+        Ok(env(PASSWORD_ENV))
+    }
+
+    /// 附件模式下放入同一封邮件的全部附件：`attachment` 在前，其后为 `attachments`
+    pub fn attachment_files(&self) -> Vec<String> {
+        self.attachment.iter().chain(&self.attachments).cloned().collect()
+    }
+
+    /// 是否使用LMTP协议投递
+    pub fn is_lmtp(&self) -> bool {
+        self.protocol.eq_ignore_ascii_case("lmtp")
+    }
+
+    /// 会话中宣告的主机名：`ehlo_name`，未设置时为本机主机名（与 mail-send 的默认值一致）
+    pub fn ehlo_hostname(&self) -> String {
+        match self.ehlo_name.as_deref().map(str::trim).filter(|name| !name.is_empty()) {
+            Some(name) => name.to_string(),
+            None => gethostname::gethostname()
+                .to_str()
+                .unwrap_or("[127.0.0.1]")
+                .to_string(),
+        }
+    }
+
+    /// 是否直接投递到收件人域名的MX主机：开启 `direct_mx` 且未设置 `smtp_server`
+    pub fn uses_direct_mx(&self) -> bool {
+        self.direct_mx && self.smtp_server.trim().is_empty()
+    }
+
+    /// 是否使用TLS会话（端口465为隐式TLS，其余端口为STARTTLS）
+    pub fn tls_enabled(&self) -> bool {
+        self.use_tls || self.require_starttls || self.port == 465
+    }
+
+    /// 实际生效的固定发送间隔（毫秒），设置了 `max_rate_per_second` 时为0
+    pub fn send_interval_ms(&self) -> u64 {
+        if self.max_rate_per_second.is_some() {
+            0
+        } else {
+            self.email_send_interval_ms
+        }
+    }
+
+    /// 建立连接的超时时间：`connect_timeout`，未设置时为 `smtp_timeout`
+    pub fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(self.connect_timeout.unwrap_or(self.smtp_timeout))
+    }
+
+    /// 发送邮件内容的超时时间：`data_timeout`，未设置时为 `smtp_timeout`
+    pub fn data_timeout(&self) -> Duration {
+        Duration::from_secs(self.data_timeout.unwrap_or(self.smtp_timeout))
+    }
+
+    /// 相邻两次发送之间是否需要等待：设置了固定间隔或间隔抖动，且未设置 `max_rate_per_second`
+    pub fn has_send_interval(&self) -> bool {
+        self.max_rate_per_second.is_none()
+            && (self.email_send_interval_ms > 0 || self.interval_jitter_ms > 0)
+    }
+
+    /// 下一次发送前等待的时长：`interval_jitter_ms` 为0时等于 `send_interval_ms`，否则在
+    /// `[间隔 - 抖动, 间隔 + 抖动]` 中均匀抽取，小于0时取0；间隔为0时抖动同样生效
+    pub fn next_send_interval(&self) -> Duration {
+        let interval = self.send_interval_ms();
+        let jitter = self.interval_jitter_ms;
+        if jitter == 0 || self.max_rate_per_second.is_some() {
+            return Duration::from_millis(interval);
+        }
+        let offset = rand::thread_rng().gen_range(0..=jitter.saturating_mul(2));
+        Duration::from_millis((interval + offset).saturating_sub(jitter))
+    }
+
     pub fn process_mode(&self) -> ProcessMode {
         if self.processes == "auto" {
             ProcessMode::Auto
@@ -198,36 +801,229 @@ impl Default for Config {
         Self {
             smtp_server: String::new(),
             port: default_port(),
+            protocol: default_protocol(),
+            ehlo_name: None,
             from: None,
             to: None,
+            bcc: None,
+            reply_to: None,
+            sender_header: None,
             envelope_cc_bcc: false,
+            recipient_allow_domains: None,
+            recipient_block_domains: None,
+            separate_envelopes: false,
+            group_by_domain: false,
+            verp: false,
+            verp_base: None,
+            recipient_from_sidecar: false,
             dir: None,
+            eml_file: None,
+            jsonl_input: None,
             extension: default_extension(),
+            decompress: false,
+            dedup: false,
+            dedup_header: false,
+            strict_parse: false,
+            fail_fast: false,
+            include_glob: None,
+            exclude_glob: None,
+            modified_after: None,
             processes: default_processes(),
+            max_connections: None,
+            per_host_connections: None,
             batch_size: default_batch_size(),
+            batch_max_bytes: None,
+            adaptive_batch: false,
+            adaptive_batch_min: default_adaptive_batch_min(),
+            adaptive_batch_max: default_adaptive_batch_max(),
             smtp_timeout: default_smtp_timeout(),
+            connect_timeout: None,
+            data_timeout: None,
+            max_run_seconds: None,
             log_level: default_log_level(),
+            log_format: default_log_format(),
+            quiet: false,
+            color: None,
+            progress: false,
             keep_headers: false,
+            envelope_from_header: false,
             anonymize_emails: false,
+            anonymize_phones: false,
+            anonymize_ips: false,
             anonymize_domain: default_anonymize_domain(),
+            anonymize_key: None,
             modify_headers: false,
+            regenerate_message_id: false,
+            preserve_date: default_preserve_date(),
+            generate_text_fallback: false,
+            output_charset: None,
             r#loop: false,
             repeat: default_repeat(),
             loop_interval: default_loop_interval(),
+            send_window: None,
             retry_interval: default_retry_interval(),
+            retry_backoff: false,
+            retry_max_interval: default_retry_max_interval(),
+            message_retries: 0,
+            retry_round: false,
+            abort_error_rate: None,
+            abort_min_samples: default_abort_min_samples(),
+            message_retry_delay_ms: default_message_retry_delay_ms(),
             attachment: None,
+            attachments: Vec::new(),
             attachment_dir: None,
+            max_attachment_bytes: None,
             subject_template: None,
             text_template: None,
             html_template: None,
+            inline_images: Vec::new(),
+            interval_jitter_ms: 0,
             email_send_interval_ms: 0,
+            max_rate_per_second: None,
+            warmup_seconds: 0,
+            warmup_start_rate: default_warmup_start_rate(),
             auth_mode: false,
             username: None,
             password: None,
+            password_file: None,
+            oauth2_token: None,
+            auth_mechanism: None,
             use_tls: false,
+            require_starttls: false,
             accept_invalid_certs: false,
+            pinned_cert_sha256: None,
+            tls_fallback: false,
+            proxy: None,
+            relays: Vec::new(),
+            relay_cooldown_secs: default_relay_cooldown_secs(),
+            direct_mx: false,
             failed_emails_dir: None,
+            move_hard_bounces: false,
+            sent_emails_dir: None,
+            move_on_success: false,
             log_file: None,
+            stats_json: None,
+            failed_csv: None,
+            report_to: None,
+            checkpoint_file: None,
+            manifest_file: None,
+            on_success_command: None,
+            on_failure_command: None,
+            pipelining: false,
+            use_bdat: false,
+            bdat_chunk_size: default_bdat_chunk_size(),
+            dkim_private_key: None,
+            dkim_selector: None,
+            dkim_domain: None,
+            reset_checkpoint: false,
+            shuffle: false,
+            shuffle_seed: None,
+            limit: None,
+            allow_empty: false,
+            dry_run: false,
+            validate_only: false,
+            count_only: false,
+            print_message: None,
+            print_message_output: None,
+            confirm: false,
+            extra_headers: Vec::new(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_file_reads_json_and_toml_with_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let json = dir.path().join("config.json");
+        fs::write(&json, r#"{"smtp_server": "mx.example.com", "port": 2525, "dry_run": true}"#).unwrap();
+        let config = Config::from_file(&json).unwrap();
+        assert_eq!(config.smtp_server, "mx.example.com");
+        assert_eq!(config.port, 2525);
+        assert!(config.dry_run);
+        assert_eq!(config.extension, default_extension());
+
+        let toml = dir.path().join("config.TOML");
+        fs::write(&toml, "dir = \"/var/mail\"\nprocesses = \"4\"\n").unwrap();
+        let config = Config::from_file(&toml).unwrap();
+        assert_eq!(config.smtp_server, "");
+        assert_eq!(config.dir.as_deref(), Some("/var/mail"));
+        assert_eq!(config.processes, "4");
+        assert_eq!(config.port, default_port());
+
+        fs::write(&json, "{ not json").unwrap();
+        assert!(Config::from_file(&json).is_err());
+    }
+
+    #[test]
+    fn test_password_sources_and_precedence() {
+        let dir = tempfile::tempdir().unwrap();
+        let password_file = dir.path().join("password");
+        fs::write(&password_file, "from-file\r\nsecond line\n").unwrap();
+        let env = |_: &str| Some("from-env".to_string());
+
+        let mut config = Config::default();
+        assert_eq!(config.resolve_password_with(|_| None).unwrap(), None);
+        assert_eq!(config.resolve_password_with(env).unwrap().as_deref(), Some("from-env"));
+
+        config.password_file = Some(password_file.to_string_lossy().to_string());
+        assert_eq!(config.resolve_password_with(env).unwrap().as_deref(), Some("from-file"));
+
+        config.password = Some("from-flag".to_string());
+        assert_eq!(config.resolve_password_with(env).unwrap().as_deref(), Some("from-flag"));
+
+        let missing = Config {
+            password_file: Some(dir.path().join("missing").to_string_lossy().to_string()),
+            ..Config::default()
+        };
+        assert!(missing.resolve_password_with(env).is_err());
+    }
+
+    #[test]
+    fn test_interval_jitter_varies_around_configured_interval() {
+        let mut config = Config {
+            email_send_interval_ms: 100,
+            ..Config::default()
+        };
+        assert_eq!(config.next_send_interval(), Duration::from_millis(100));
+
+        config.interval_jitter_ms = 50;
+        let samples: Vec<u128> = (0..2000).map(|_| config.next_send_interval().as_millis()).collect();
+        assert!(samples.iter().all(|ms| (50..=150).contains(ms)));
+        let mean = samples.iter().sum::<u128>() as f64 / samples.len() as f64;
+        assert!((mean - 100.0).abs() < 5.0, "mean {}", mean);
+        assert!(samples.iter().any(|&ms| ms != samples[0]));
+
+        // 抖动大于间隔时，低于0的部分取0
+        config.interval_jitter_ms = 500;
+        assert!((0..200).any(|_| config.next_send_interval() == Duration::ZERO));
+    }
+
+    #[test]
+    fn test_interval_jitter_applies_without_base_interval() {
+        let mut config = Config {
+            interval_jitter_ms: 50,
+            ..Config::default()
+        };
+        assert!(config.has_send_interval());
+        let samples: Vec<u128> = (0..2000).map(|_| config.next_send_interval().as_millis()).collect();
+        assert!(samples.iter().all(|ms| *ms <= 50));
+        assert!(samples.iter().any(|ms| *ms > 0));
+
+        // 速率上限与固定间隔互斥，抖动也随之失效
+        config.max_rate_per_second = Some(10.0);
+        assert!(!config.has_send_interval());
+        assert_eq!(config.next_send_interval(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_password_read_from_environment() {
+        // 不修改进程环境变量，避免与并行运行的测试互相干扰
+        let resolved = Config::default()
+            .resolve_password_with(|name| (name == "RSENDMAIL_SMTP_PASSWORD").then(|| "env-secret".to_string()));
+        assert_eq!(resolved.unwrap().as_deref(), Some("env-secret"));
+    }
+}
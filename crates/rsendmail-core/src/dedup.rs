@@ -0,0 +1,142 @@
+//! 按内容去重：同一次发送中内容完全相同的邮件只发送一封
+//!
+//! 启用 `dedup` 后，各进程组在把文件加入批次前计算其内容（解压、匿名化之后）的
+//! SHA-256，所有进程组共享同一个 [`ContentDedup`]。相同内容已发送成功时跳过当前文件并计入
+//! `Stats::duplicates_skipped`；第一个文件尚未有结果时暂缓当前文件，第一个文件发送成功后
+//! 暂缓的文件计为重复，发送失败时交回调用方重新排队，相同内容不会因第一封失败而丢失。
+//!
+//! 启用 `dedup_header` 时，同一哈希以 `X-RSendMail-Dedup` 邮件头写入发出的邮件，
+//! 重新运行时接收方可据此识别并丢弃已收到的邮件。
+
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// `dedup_header` 写入的邮件头名称
+pub const DEDUP_HEADER: &str = "X-RSendMail-Dedup";
+
+/// 内容的 SHA-256 十六进制表示，相同内容在每次运行中得到相同的值
+pub fn content_hash(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// 一个内容哈希的登记状态
+enum Entry {
+    /// 第一个文件已排队、尚未有结果；`held` 为期间遇到的相同内容的其他文件
+    Pending { path: String, held: Vec<String> },
+    /// 第一个文件已发送成功
+    Sent(String),
+}
+
+/// [`ContentDedup::claim`] 的结果
+#[derive(Debug, PartialEq, Eq)]
+pub enum Claim {
+    /// 当前文件是该内容的第一个文件，应当发送
+    First,
+    /// 相同内容的文件尚未有结果，当前文件暂缓，由该文件的结果决定是否发送
+    Held(String),
+    /// 相同内容已由该文件发送成功，当前文件跳过
+    Duplicate(String),
+}
+
+#[derive(Default)]
+struct Registry {
+    entries: HashMap<[u8; 32], Entry>,
+    /// 第一个文件的路径 → 其内容哈希
+    firsts: HashMap<String, [u8; 32]>,
+}
+
+/// 本次发送中已登记的内容哈希，在所有进程组间共享
+#[derive(Default)]
+pub struct ContentDedup {
+    registry: Mutex<Registry>,
+}
+
+impl ContentDedup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记 `path` 的内容
+    ///
+    /// 同一文件再次登记（如批次中途更换连接后重新排队）仍为 [`Claim::First`]。
+    pub fn claim(&self, path: &str, content: &[u8]) -> Claim {
+        let hash: [u8; 32] = Sha256::digest(content).into();
+        let mut registry = self.registry.lock().unwrap();
+        match registry.entries.get_mut(&hash) {
+            Some(Entry::Sent(first)) => Claim::Duplicate(first.clone()),
+            Some(Entry::Pending { path: first, .. }) if first == path => Claim::First,
+            Some(Entry::Pending { path: first, held }) => {
+                held.push(path.to_string());
+                Claim::Held(first.clone())
+            }
+            None => {
+                registry.entries.insert(
+                    hash,
+                    Entry::Pending {
+                        path: path.to_string(),
+                        held: Vec::new(),
+                    },
+                );
+                registry.firsts.insert(path.to_string(), hash);
+                Claim::First
+            }
+        }
+    }
+
+    /// `path` 已发送成功，返回因此跳过的暂缓文件数
+    pub fn sent(&self, path: &str) -> usize {
+        let mut registry = self.registry.lock().unwrap();
+        let Some(hash) = registry.firsts.remove(path) else {
+            return 0;
+        };
+        match registry.entries.insert(hash, Entry::Sent(path.to_string())) {
+            Some(Entry::Pending { held, .. }) => held.len(),
+            _ => 0,
+        }
+    }
+
+    /// `path` 最终发送失败，撤销其登记并返回暂缓的文件，调用方应将其重新排队
+    pub fn failed(&self, path: &str) -> Vec<String> {
+        let mut registry = self.registry.lock().unwrap();
+        let Some(hash) = registry.firsts.remove(path) else {
+            return Vec::new();
+        };
+        match registry.entries.remove(&hash) {
+            Some(Entry::Pending { held, .. }) => held,
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_claim_holds_copies_until_first_copy_is_resolved() {
+        let dedup = ContentDedup::new();
+        assert_eq!(dedup.claim("a.eml", b"same"), Claim::First);
+        assert_eq!(dedup.claim("b.eml", b"other"), Claim::First);
+        assert_eq!(dedup.claim("c.eml", b"same"), Claim::Held("a.eml".to_string()));
+        assert_eq!(dedup.claim("a.eml", b"same"), Claim::First);
+
+        // 第一封失败：暂缓的文件交回，重新登记后成为新的第一个文件
+        assert_eq!(dedup.failed("a.eml"), vec!["c.eml".to_string()]);
+        assert_eq!(dedup.claim("c.eml", b"same"), Claim::First);
+        assert_eq!(dedup.claim("d.eml", b"same"), Claim::Held("c.eml".to_string()));
+        assert_eq!(dedup.sent("c.eml"), 1);
+        assert_eq!(dedup.claim("e.eml", b"same"), Claim::Duplicate("c.eml".to_string()));
+        assert_eq!(dedup.sent("b.eml"), 0);
+    }
+
+    #[test]
+    fn test_content_hash_is_stable_hex_digest() {
+        assert_eq!(content_hash(b"same"), content_hash(b"same"));
+        assert_ne!(content_hash(b"same"), content_hash(b"other"));
+        assert_eq!(content_hash(b"").len(), 64);
+    }
+}
@@ -0,0 +1,121 @@
+//! 发送前的预估摘要
+//!
+//! [`SendEstimate`] 由 `Mailer::estimate` 生成：只扫描目录并读取文件元数据，不读取邮件内容。
+//! 预计耗时只考虑配置中的限速（`max_rate_per_second` 及预热期）或固定发送间隔，
+//! 未配置任何节流时发送耗时取决于服务器，不做估计。
+
+use rsendmail_i18n::{tr, tr_with_args};
+use std::collections::HashSet;
+use std::fmt;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::rate_limit::RateLimiter;
+use crate::throughput::format_hms;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SendEstimate {
+    /// 将要发送的文件数量（已应用过滤、检查点与 `limit`）
+    pub files: usize,
+    /// 文件大小之和（字节）
+    pub total_bytes: u64,
+    /// 不同收件人地址的数量；收件人取自各EML文件时为 None
+    pub recipients: Option<usize>,
+    /// 按限速或发送间隔估计的耗时；未配置节流时为 None
+    pub projected_duration: Option<Duration>,
+}
+
+/// `to` 与 `bcc` 中不同收件人的数量（不区分大小写）；收件人来自EML或收件人文件时为 None
+pub(crate) fn configured_recipients(config: &Config) -> Option<usize> {
+    if config.recipient_from_sidecar {
+        return None;
+    }
+    let to = config.to.as_deref().filter(|to| !to.trim().is_empty())?;
+    let distinct: HashSet<String> = to
+        .split(',')
+        .chain(config.bcc.as_deref().unwrap_or_default().split(','))
+        .map(|address| address.trim().to_ascii_lowercase())
+        .filter(|address| !address.is_empty())
+        .collect();
+    Some(distinct.len())
+}
+
+/// 按配置估计发送 `files` 个文件的耗时
+///
+/// 设置了 `max_rate_per_second` 时按所有进程组共享的限速计算；否则每个进程组
+/// 在批次之间等待 `email_send_interval_ms`，耗时取决于文件最多的进程组。
+pub(crate) fn projected_duration(config: &Config, files: usize, processes: usize) -> Option<Duration> {
+    if let Some(limiter) = RateLimiter::from_config(config) {
+        return Some(limiter.duration_for(files));
+    }
+    let interval = config.send_interval_ms();
+    if interval == 0 || files == 0 {
+        return None;
+    }
+    let per_process = files.div_ceil(processes.max(1));
+    let batches = per_process.div_ceil(config.batch_size.max(1));
+    Some(Duration::from_millis(interval * (batches as u64 - 1)))
+}
+
+impl fmt::Display for SendEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", tr("core.estimate.title"))?;
+        writeln!(
+            f,
+            "{}",
+            tr_with_args("core.estimate.files", &[("count", &self.files.to_string())])
+        )?;
+        writeln!(
+            f,
+            "{}",
+            tr_with_args(
+                "core.estimate.total_bytes",
+                &[("bytes", &self.total_bytes.to_string())]
+            )
+        )?;
+        match self.recipients {
+            Some(count) => writeln!(
+                f,
+                "{}",
+                tr_with_args("core.estimate.recipients", &[("count", &count.to_string())])
+            )?,
+            None => writeln!(f, "{}", tr("core.estimate.recipients_from_eml"))?,
+        }
+        match self.projected_duration {
+            Some(duration) => write!(
+                f,
+                "{}",
+                tr_with_args("core.estimate.duration", &[("duration", &format_hms(duration))])
+            ),
+            None => write!(f, "{}", tr("core.estimate.duration_unbounded")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_projection_uses_rate_limit_or_batch_interval() {
+        let config = Config {
+            max_rate_per_second: Some(10.0),
+            ..Config::default()
+        };
+        assert_eq!(projected_duration(&config, 101, 4), Some(Duration::from_secs(10)));
+
+        // 每个进程组 25 个文件，每批 10 个共 3 批，批次之间等待两次
+        let config = Config {
+            email_send_interval_ms: 500,
+            batch_size: 10,
+            ..Config::default()
+        };
+        assert_eq!(projected_duration(&config, 100, 4), Some(Duration::from_secs(1)));
+
+        let config = Config {
+            email_send_interval_ms: 0,
+            ..Config::default()
+        };
+        assert_eq!(projected_duration(&config, 100, 4), None);
+    }
+}
@@ -0,0 +1,94 @@
+//! 每封邮件发送后运行的外部命令
+//!
+//! 设置 `on_success_command` / `on_failure_command` 后，EML 批量发送每成功或失败一封
+//! 邮件就运行一次对应的命令，命令中的 `{file}` 替换为该邮件的文件路径。命令按空白
+//! 分割为程序与参数后直接执行，不经过shell，文件名中的空格或特殊字符不会被解释。
+//!
+//! 命令在后台任务中运行，不阻塞发送；同时运行的命令数量不超过 [`MAX_CONCURRENT_HOOKS`]，
+//! 超出的命令排队等待。发送结束时等待所有命令完成。命令无法启动或以非零状态退出时
+//! 记录警告，不影响发送结果。
+
+use std::sync::{Arc, Mutex};
+
+use log::{info, warn};
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::config::Config;
+use crate::progress::ProgressObserver;
+
+/// 同时运行的钩子命令数量上限
+pub const MAX_CONCURRENT_HOOKS: usize = 4;
+
+pub struct Hooks {
+    on_success: Option<String>,
+    on_failure: Option<String>,
+    slots: Arc<Semaphore>,
+    tasks: Mutex<JoinSet<()>>,
+}
+
+impl Hooks {
+    /// 配置了任一钩子命令时创建；演练模式不运行钩子
+    pub fn from_config(config: &Config) -> Option<Self> {
+        let command = |command: &Option<String>| {
+            command
+                .as_deref()
+                .map(str::trim)
+                .filter(|command| !command.is_empty())
+                .map(str::to_string)
+        };
+        let on_success = command(&config.on_success_command);
+        let on_failure = command(&config.on_failure_command);
+        if config.dry_run || (on_success.is_none() && on_failure.is_none()) {
+            return None;
+        }
+        Some(Self {
+            on_success,
+            on_failure,
+            slots: Arc::new(Semaphore::new(MAX_CONCURRENT_HOOKS)),
+            tasks: Mutex::new(JoinSet::new()),
+        })
+    }
+
+    /// 在后台运行 `command`，`{file}` 替换为 `file`
+    fn spawn(&self, command: &str, file: &str) {
+        let mut words = command.split_whitespace().map(|word| word.replace("{file}", file));
+        let Some(program) = words.next() else {
+            return;
+        };
+        let args: Vec<String> = words.collect();
+        let slots = self.slots.clone();
+        self.tasks.lock().unwrap().spawn(async move {
+            let Ok(_permit) = slots.acquire_owned().await else {
+                return;
+            };
+            match Command::new(&program).args(&args).status().await {
+                Ok(status) if status.success() => info!("钩子命令执行完成: {} {:?}", program, args),
+                Ok(status) => warn!("钩子命令以 {} 退出: {} {:?}", status, program, args),
+                Err(e) => warn!("无法启动钩子命令 {}: {}", program, e),
+            }
+        });
+    }
+
+    /// 等待所有已启动的命令完成
+    pub async fn wait(&self) {
+        let mut tasks = std::mem::take(&mut *self.tasks.lock().unwrap());
+        while tasks.join_next().await.is_some() {}
+    }
+}
+
+/// 按每封邮件的结果运行钩子命令
+impl ProgressObserver for Hooks {
+    fn email_sent(&self, path: &str) {
+        if let Some(command) = &self.on_success {
+            self.spawn(command, path);
+        }
+    }
+
+    fn email_failed(&self, path: &str, _error: &str) {
+        if let Some(command) = &self.on_failure {
+            self.spawn(command, path);
+        }
+    }
+}
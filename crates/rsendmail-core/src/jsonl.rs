@@ -0,0 +1,224 @@
+//! JSON Lines 输入：每行一个 JSON 对象，描述一封待构建并发送的邮件
+//!
+//! 每个对象包含 `to`（逗号分隔的地址字符串或地址数组）、`subject`、`body`，以及可选的
+//! `attachments`（附件文件路径数组），发件人统一使用 `from`。输入按行流式读取，每
+//! [`CHUNK_RECORDS`] 条记录用 MessageBuilder 构建为EML并写入临时目录（[`SpoolDir`]），
+//! 再交给与EML目录相同的批量发送流程，复用连接、并发与速率限制；发送完一段后再读取下一段，
+//! 标准输入等大输入不必整个载入内存。无法解析或构建的行记录日志并计入 `parse_errors`。
+//!
+//! 统计、进度回调、检查点与投递清单中的记录都以 `来源:行号` 标识，而不是临时EML的路径；
+//! 保存失败邮件时副本的文件名同样带有来源文件名与行号。
+
+use mail_send::mail_builder::MessageBuilder;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::progress::{Delivery, SendProgress};
+
+/// 每段构建并发送的记录数
+pub const CHUNK_RECORDS: usize = 1000;
+
+/// JSONL 中的一条邮件记录
+#[derive(Debug, Deserialize)]
+pub struct JsonlMessage {
+    pub to: Recipients,
+    pub subject: String,
+    pub body: String,
+    #[serde(default)]
+    pub attachments: Vec<String>,
+}
+
+/// `to` 字段：逗号分隔的字符串或字符串数组
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Recipients {
+    Joined(String),
+    List(Vec<String>),
+}
+
+impl Recipients {
+    pub fn addresses(&self) -> Vec<String> {
+        let addresses: Vec<&str> = match self {
+            Recipients::Joined(joined) => joined.split(',').collect(),
+            Recipients::List(list) => list.iter().map(String::as_str).collect(),
+        };
+        addresses
+            .into_iter()
+            .map(str::trim)
+            .filter(|address| !address.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+impl JsonlMessage {
+    /// 以 `from` 为发件人构建完整的EML，附件的MIME类型按内容推断
+    pub fn build(&self, from: &str) -> io::Result<Vec<u8>> {
+        let to = self.to.addresses();
+        if to.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "missing recipients"));
+        }
+        let mut builder = MessageBuilder::new()
+            .from(from)
+            .to(to)
+            .subject(self.subject.as_str())
+            .text_body(self.body.as_str());
+        for path in &self.attachments {
+            let data = fs::read(path)
+                .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path, e)))?;
+            let content_type = infer::get(&data).map_or("application/octet-stream", |kind| kind.mime_type());
+            let filename = Path::new(path)
+                .file_name()
+                .map_or_else(|| path.clone(), |name| name.to_string_lossy().to_string());
+            builder = builder.attachment(content_type, filename, data);
+        }
+        builder.write_to_vec()
+    }
+}
+
+/// 按行读取的记录，产出 (行号, 解析结果)，空行跳过
+pub struct Records {
+    lines: std::iter::Enumerate<io::Lines<Box<dyn BufRead + Send>>>,
+}
+
+impl Iterator for Records {
+    type Item = io::Result<(usize, Result<JsonlMessage, String>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, line) in self.lines.by_ref() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e)),
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            return Some(Ok((index + 1, serde_json::from_str(&line).map_err(|e| e.to_string()))));
+        }
+        None
+    }
+}
+
+/// 打开 `source`（`-` 为标准输入），记录在迭代时才逐行读取
+pub fn read_records(source: &str) -> io::Result<Records> {
+    let reader: Box<dyn BufRead + Send> = if source == "-" {
+        Box::new(BufReader::new(io::stdin()))
+    } else {
+        Box::new(BufReader::new(fs::File::open(source)?))
+    };
+    Ok(Records {
+        lines: reader.lines().enumerate(),
+    })
+}
+
+/// 记录的位置 `来源:行号`，用于错误信息、检查点与投递清单
+pub fn location(source: &str, line: usize) -> String {
+    format!("{}:{}", source, line)
+}
+
+/// 记录在临时目录中的EML文件名，带有来源文件名（标准输入为 `stdin`）与行号
+pub fn spool_file_name(source: &str, line: usize) -> String {
+    let name = match Path::new(source).file_name() {
+        Some(name) if source != "-" => name.to_string_lossy(),
+        _ => "stdin".into(),
+    };
+    format!("{}-line-{:06}.eml", name, line)
+}
+
+/// 将进度回调中的临时EML路径替换为 `来源:行号` 后转发
+pub(crate) struct LabelledProgress {
+    pub labels: Arc<HashMap<String, String>>,
+    pub inner: Arc<dyn SendProgress>,
+}
+
+impl LabelledProgress {
+    fn label<'a>(&'a self, path: &'a str) -> &'a str {
+        self.labels.get(path).map_or(path, String::as_str)
+    }
+}
+
+impl SendProgress for LabelledProgress {
+    fn on_email_sent(&self, path: &str, duration: Duration) {
+        self.inner.on_email_sent(self.label(path), duration);
+    }
+
+    fn on_email_failed(&self, path: &str, error: &str) {
+        self.inner.on_email_failed(self.label(path), error);
+    }
+
+    fn on_batch_start(&self, group: usize, size: usize) {
+        self.inner.on_batch_start(group, size);
+    }
+
+    fn on_delivery(&self, delivery: &Delivery<'_>) {
+        self.inner.on_delivery(&Delivery {
+            path: self.label(delivery.path),
+            ..*delivery
+        });
+    }
+
+    fn on_throughput(&self, qps: f64, eta: Option<Duration>) {
+        self.inner.on_throughput(qps, eta);
+    }
+}
+
+/// 存放构建好的EML的临时目录，离开作用域时删除
+pub struct SpoolDir(PathBuf);
+
+impl SpoolDir {
+    pub fn create() -> io::Result<Self> {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!("rsendmail-jsonl-{}-{}", std::process::id(), nanos));
+        fs::create_dir_all(&path)?;
+        Ok(Self(path))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for SpoolDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_records_reports_malformed_lines_by_number() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("messages.jsonl");
+        fs::write(
+            &path,
+            concat!(
+                r#"{"to":"a@example.com, b@example.com","subject":"s","body":"b"}"#,
+                "\n\n{not json}\n",
+                r#"{"to":["c@example.com"],"subject":"s","body":"b"}"#,
+                "\n"
+            ),
+        )
+        .unwrap();
+
+        let records: Vec<_> = read_records(&path.to_string_lossy())
+            .unwrap()
+            .collect::<io::Result<_>>()
+            .unwrap();
+
+        let lines: Vec<_> = records.iter().map(|(line, record)| (*line, record.is_ok())).collect();
+        assert_eq!(lines, [(1, true), (3, false), (4, true)]);
+        let first = records[0].1.as_ref().unwrap();
+        assert_eq!(first.to.addresses(), ["a@example.com", "b@example.com"]);
+    }
+}
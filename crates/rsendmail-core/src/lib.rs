@@ -3,13 +3,45 @@
 //! 这是 RSendMail 的核心库，提供邮件发送的核心功能。
 //! 可以被 CLI 和 GUI 应用共享使用。
 
+pub mod adaptive_batch;
 pub mod anonymizer;
+pub mod attachment;
+pub mod backoff;
+pub mod charset;
+pub mod checkpoint;
+pub mod circuit_breaker;
+pub mod compression;
 pub mod config;
+pub mod dedup;
+pub mod dkim;
+pub mod estimate;
+pub mod hooks;
+pub mod jsonl;
 pub mod mailer;
+#[cfg(test)]
+pub(crate) mod mock_smtp;
+pub mod manifest;
+pub mod mx;
+pub mod progress;
+pub mod proxy;
+pub mod rate_limit;
+pub mod recipient_filter;
+pub mod relay;
+pub mod send_window;
+pub mod shutdown;
 pub mod stats;
+pub mod stop_on_failure;
+pub mod throughput;
+pub mod tls;
+pub mod transport;
+pub mod validate;
 
 // 重新导出主要类型
 pub use anonymizer::EmailAnonymizer;
 pub use config::{Config, ProcessMode};
-pub use mailer::Mailer;
-pub use stats::Stats;
+pub use estimate::SendEstimate;
+pub use mailer::{Mailer, NoInputFiles};
+pub use progress::{Delivery, FailureKind, SendProgress};
+pub use shutdown::{RunControl, RunState};
+pub use stats::{FailureClass, Stats};
+pub use validate::ValidationReport;
@@ -0,0 +1,105 @@
+//! 逐封邮件的投递清单
+//!
+//! 设置 `manifest_file` 后，EML 批量发送每结束一次投递尝试（无论成功或失败）就向清单
+//! 追加一行，字段以制表符分隔：源文件路径、信封发件人、信封收件人（逗号分隔）、
+//! 结果（`sent`/`failed`）、服务器应答。与只记录成功文件的检查点不同，清单保留每次
+//! 尝试的收件人与应答，用于审计。
+//!
+//! 各进程组共享同一个 [`Manifest`]，写入经互斥锁串行化，行与行之间不会交错；
+//! 每行写入后立即刷新，进程崩溃时不会丢失已完成的记录。
+
+use std::borrow::Cow;
+use std::fs::{File, OpenOptions};
+use std::io::{self, LineWriter, Write};
+use std::sync::Mutex;
+
+use log::warn;
+
+use crate::progress::{Delivery, ProgressObserver};
+
+/// 字段中的制表符与换行会破坏按行、按列存储的格式，替换为空格
+fn field(value: &str) -> Cow<'_, str> {
+    if value.contains(['\t', '\r', '\n']) {
+        Cow::Owned(value.replace(['\t', '\r', '\n'], " "))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+pub struct Manifest {
+    writer: Mutex<LineWriter<File>>,
+}
+
+impl Manifest {
+    /// 以追加方式打开清单文件，不存在时创建
+    pub fn open(path: &str) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            writer: Mutex::new(LineWriter::new(file)),
+        })
+    }
+
+    /// 记录一次投递尝试
+    pub fn record(&self, delivery: &Delivery<'_>) -> io::Result<()> {
+        let line = format!(
+            "{}\t{}\t{}\t{}\t{}\n",
+            field(delivery.path),
+            field(delivery.envelope_from.unwrap_or_default()),
+            field(&delivery.recipients.join(",")),
+            if delivery.sent { "sent" } else { "failed" },
+            field(delivery.response)
+        );
+        self.writer.lock().unwrap().write_all(line.as_bytes())
+    }
+}
+
+/// 把每次投递尝试记入清单
+impl ProgressObserver for Manifest {
+    fn delivery(&self, delivery: &Delivery<'_>) {
+        if let Err(e) = self.record(delivery) {
+            warn!("写入投递清单失败: {}: {}", delivery.path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress::FailureKind;
+
+    #[test]
+    fn test_fields_are_tab_separated_and_sanitized() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("manifest.tsv");
+        let path = path.to_str().unwrap();
+
+        let manifest = Manifest::open(path).unwrap();
+        let recipients = ["a@example.com".to_string(), "b@example.com".to_string()];
+        manifest
+            .record(&Delivery {
+                path: "/mail/a\tb.eml",
+                envelope_from: Some("sender@example.com"),
+                recipients: &recipients,
+                sent: false,
+                response: "451 4.3.0 Try\r\nagain",
+                failure: Some(FailureKind::Send),
+            })
+            .unwrap();
+        manifest
+            .record(&Delivery {
+                path: "/mail/c.eml",
+                envelope_from: None,
+                recipients: &[],
+                sent: false,
+                response: "SMTP连接超时",
+                failure: Some(FailureKind::Send),
+            })
+            .unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(path).unwrap(),
+            "/mail/a b.eml\tsender@example.com\ta@example.com,b@example.com\tfailed\t451 4.3.0 Try  again\n\
+             /mail/c.eml\t\t\tfailed\tSMTP连接超时\n"
+        );
+    }
+}
@@ -0,0 +1,366 @@
+//! 测试用的进程内SMTP服务端
+//!
+//! [`MockSmtpServer`] 实现最小化的 EHLO/MAIL/RCPT/DATA/BDAT/RSET/QUIT 应答，可通过内存管道
+//! （`connect`）、本地TCP端口（`listen`）或Unix域套接字（`listen_unix`）连接，记录收到的
+//! 命令与每封邮件的内容。应答可按字段定制：拒绝指定收件人、替换或延迟DATA应答、注入临时性
+//! 失败或在第n封邮件后回复421断开，供各模块的测试端到端驱动 `Mailer`。
+
+use mail_send::SmtpClient;
+use std::fs;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{
+    AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader, DuplexStream,
+    ReadBuf,
+};
+
+use crate::config::Config;
+use crate::shutdown::RunControl;
+
+pub(crate) const TEST_EML: &str =
+    "From: sender@example.com\r\nTo: rcpt@example.com\r\nSubject: test\r\n\r\nbody\r\n.dotted line\r\n";
+
+/// 基于内存管道的最小化SMTP服务端：除 `rejected_recipients` 中地址的 RCPT TO 外
+/// 所有命令都返回成功，并记录收到的命令和每次DATA的内容
+#[derive(Clone, Default)]
+pub(crate) struct MockSmtpServer {
+    pub(crate) rejected_recipients: Arc<Vec<String>>,
+    pub(crate) connections: Arc<AtomicUsize>,
+    /// 当前仍在会话中的连接数，以及会话期间出现过的最大值
+    pub(crate) open_connections: Arc<AtomicUsize>,
+    pub(crate) peak_connections: Arc<AtomicUsize>,
+    pub(crate) commands: Arc<Mutex<Vec<String>>>,
+    pub(crate) messages: Arc<Mutex<Vec<Vec<u8>>>>,
+    /// 收到每封邮件内容后、回复250之前，对该句柄请求停止的次数
+    pub(crate) stop_on_message: Option<(RunControl, usize)>,
+    /// 收到邮件内容后、回复DATA应答之前等待的时间，模拟处理缓慢的服务器
+    pub(crate) data_delay: Option<Duration>,
+    /// 替代DATA结束后默认的 "250 OK" 应答
+    pub(crate) data_reply: Option<String>,
+    /// DATA结束后先回复 "451" 临时性失败的剩余次数，用完后按正常应答
+    pub(crate) transient_data_failures: Arc<AtomicUsize>,
+    /// 收到第n封邮件（跨连接计数，从1开始）后回复421并关闭连接
+    pub(crate) closing_on_message: Option<usize>,
+    /// 收到第n封邮件（跨连接计数，从1开始）后回复554拒绝该邮件，连接保持可用
+    pub(crate) rejecting_message: Option<usize>,
+    /// 单个批次（同一连接上以RSET衔接的连续事务）接受的事务数上限，超出后对 MAIL FROM 回复451
+    pub(crate) messages_per_batch: Option<usize>,
+    /// 替代 `listen` 发送的默认问候语
+    pub(crate) greeting: Option<String>,
+    /// `listen` 接受连接后等待该时长再发送问候语，模拟建立连接缓慢的服务器
+    pub(crate) greeting_delay: Option<Duration>,
+    /// 在EHLO应答中通告 PIPELINING
+    pub(crate) pipelining: bool,
+    /// 在EHLO应答中通告 CHUNKING，并接受 `BDAT <n> [LAST]` 分块，LAST 时记录拼接后的邮件
+    pub(crate) chunking: bool,
+    /// 在EHLO应答中通告的 AUTH 机制（如 "XOAUTH2 PLAIN"），AUTH 命令一律回复 235
+    pub(crate) auth_mechanisms: Option<&'static str>,
+    /// 服务端从连接上读到数据的次数
+    pub(crate) reads: Arc<AtomicUsize>,
+    /// 按LMTP应答：DATA结束后为每个已接受的收件人各回复一行，
+    /// 列表中的收件人回复 452，其余回复 250
+    pub(crate) lmtp_failed_recipients: Option<Arc<Vec<String>>>,
+}
+
+/// 统计读到数据的次数的读取端包装
+struct CountingReader<R> {
+    inner: R,
+    reads: Arc<AtomicUsize>,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for CountingReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let filled = buf.filled().len();
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if buf.filled().len() > filled {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+        }
+        poll
+    }
+}
+
+impl MockSmtpServer {
+    pub(crate) fn rejecting(recipients: &[&str]) -> Self {
+        Self {
+            rejected_recipients: Arc::new(recipients.iter().map(|r| r.to_string()).collect()),
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn connect(&self) -> SmtpClient<DuplexStream> {
+        self.connections.fetch_add(1, Ordering::SeqCst);
+        let (client_stream, server_stream) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(self.clone().serve(server_stream));
+        SmtpClient {
+            stream: client_stream,
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    pub(crate) fn commands_starting_with(&self, prefix: &str) -> usize {
+        self.commands
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|c| c.starts_with(prefix))
+            .count()
+    }
+
+    /// 在本地端口监听TCP连接，发送问候语后按与 `connect` 相同的逻辑应答，返回端口
+    pub(crate) async fn listen(&self) -> u16 {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = self.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                server.connections.fetch_add(1, Ordering::SeqCst);
+                let server = server.clone();
+                tokio::spawn(async move {
+                    if let Some(delay) = server.greeting_delay {
+                        tokio::time::sleep(delay).await;
+                    }
+                    let greeting = server.greeting.as_deref().unwrap_or("220 mock.test ESMTP");
+                    if stream
+                        .write_all(format!("{}\r\n", greeting).as_bytes())
+                        .await
+                        .is_ok()
+                    {
+                        server.serve(stream).await;
+                    }
+                });
+            }
+        });
+        port
+    }
+
+    /// 在Unix域套接字上监听，行为与 `listen` 相同
+    #[cfg(unix)]
+    pub(crate) fn listen_unix(&self, path: &Path) {
+        let listener = tokio::net::UnixListener::bind(path).unwrap();
+        let server = self.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                server.connections.fetch_add(1, Ordering::SeqCst);
+                stream.write_all(b"220 mock.test LMTP\r\n").await.unwrap();
+                tokio::spawn(server.clone().serve(stream));
+            }
+        });
+    }
+
+    async fn serve<S: AsyncRead + AsyncWrite + Send + 'static>(self, stream: S) {
+        let open = self.open_connections.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak_connections.fetch_max(open, Ordering::SeqCst);
+        self.session(stream).await;
+        self.open_connections.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    async fn session<S: AsyncRead + AsyncWrite + Send + 'static>(&self, stream: S) {
+        let (reader, mut writer) = tokio::io::split(stream);
+        let mut reader = BufReader::new(CountingReader {
+            inner: reader,
+            reads: self.reads.clone(),
+        });
+        let mut line = Vec::new();
+        // 当前事务中已接受的收件人
+        let mut accepted = Vec::new();
+        // 当前事务中已收到的BDAT分块
+        let mut chunks = Vec::new();
+        // 当前批次（以RSET衔接的连续事务）中收到的 MAIL FROM 数
+        let mut batch_transactions = 0;
+        // 上一条命令是否为RSET
+        let mut after_rset = false;
+        loop {
+            line.clear();
+            if reader.read_until(b'\n', &mut line).await.unwrap_or(0) == 0 {
+                break;
+            }
+            let command = String::from_utf8_lossy(&line).trim_end().to_string();
+            self.commands.lock().unwrap().push(command.clone());
+            let upper = command.to_ascii_uppercase();
+            let follows_rset = std::mem::replace(&mut after_rset, upper == "RSET");
+            if upper.starts_with("MAIL FROM:") {
+                accepted.clear();
+                batch_transactions = if follows_rset { batch_transactions + 1 } else { 1 };
+                if self.messages_per_batch.is_some_and(|limit| batch_transactions > limit) {
+                    writer.write_all(b"451 4.7.0 Too many messages in this batch\r\n").await.unwrap();
+                    continue;
+                }
+            }
+            if upper == "DATA" {
+                writer.write_all(b"354 Start mail input\r\n").await.unwrap();
+                let mut payload = Vec::new();
+                loop {
+                    line.clear();
+                    if reader.read_until(b'\n', &mut line).await.unwrap_or(0) == 0
+                        || line == b".\r\n"
+                    {
+                        break;
+                    }
+                    // 撤销透明处理（dot-stuffing）
+                    let unstuffed = if line.starts_with(b"..") { &line[1..] } else { &line[..] };
+                    payload.extend_from_slice(unstuffed);
+                }
+                // 去掉结束标记 "\r\n.\r\n" 前客户端追加的 CRLF
+                payload.truncate(payload.len().saturating_sub(2));
+                let received = {
+                    let mut messages = self.messages.lock().unwrap();
+                    messages.push(payload);
+                    messages.len()
+                };
+                if let Some(delay) = self.data_delay {
+                    tokio::time::sleep(delay).await;
+                }
+                if self.closing_on_message == Some(received) {
+                    let _ = writer.write_all(b"421 4.3.2 Closing connection\r\n").await;
+                    break;
+                }
+                if self.rejecting_message == Some(received) {
+                    writer.write_all(b"554 5.7.1 Message rejected\r\n").await.unwrap();
+                    continue;
+                }
+                if let Some((control, stops)) = &self.stop_on_message {
+                    for _ in 0..*stops {
+                        control.request_stop();
+                    }
+                }
+                if let Some(failed) = &self.lmtp_failed_recipients {
+                    for recipient in accepted.drain(..) {
+                        let reply = if failed.contains(&recipient) {
+                            format!("452 4.2.2 <{}> Mailbox full\r\n", recipient)
+                        } else {
+                            format!("250 2.0.0 <{}> Saved\r\n", recipient)
+                        };
+                        writer.write_all(reply.as_bytes()).await.unwrap();
+                    }
+                    continue;
+                }
+                let transient = self
+                    .transient_data_failures
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                    .is_ok();
+                let reply = if transient {
+                    "451 4.3.0 Try again later"
+                } else {
+                    self.data_reply.as_deref().unwrap_or("250 OK")
+                };
+                writer
+                    .write_all(format!("{}\r\n", reply).as_bytes())
+                    .await
+                    .unwrap();
+            } else if upper.starts_with("BDAT ") {
+                let mut args = upper.split_whitespace().skip(1);
+                let size: usize = args.next().and_then(|n| n.parse().ok()).unwrap_or(0);
+                let mut chunk = vec![0; size];
+                if reader.read_exact(&mut chunk).await.is_err() {
+                    break;
+                }
+                chunks.extend_from_slice(&chunk);
+                if args.next() == Some("LAST") {
+                    self.messages.lock().unwrap().push(std::mem::take(&mut chunks));
+                }
+                writer.write_all(b"250 OK\r\n").await.unwrap();
+            } else if upper == "QUIT" {
+                writer.write_all(b"221 Bye\r\n").await.unwrap();
+                break;
+            } else if upper.starts_with("EHLO")
+                && (self.pipelining || self.chunking || self.auth_mechanisms.is_some())
+            {
+                let mut extensions = Vec::new();
+                if self.pipelining {
+                    extensions.push("PIPELINING".to_string());
+                }
+                if self.chunking {
+                    extensions.push("CHUNKING".to_string());
+                }
+                if let Some(mechanisms) = self.auth_mechanisms {
+                    extensions.push(format!("AUTH {}", mechanisms));
+                }
+                let mut reply = String::from("250-mock.test\r\n");
+                for (n, extension) in extensions.iter().enumerate() {
+                    let separator = if n + 1 == extensions.len() { ' ' } else { '-' };
+                    reply.push_str(&format!("250{}{}\r\n", separator, extension));
+                }
+                writer.write_all(reply.as_bytes()).await.unwrap();
+            } else if upper.starts_with("AUTH ") {
+                writer
+                    .write_all(b"235 2.7.0 Authentication successful\r\n")
+                    .await
+                    .unwrap();
+            } else if upper.starts_with("RCPT TO:")
+                && self
+                    .rejected_recipients
+                    .iter()
+                    .any(|r| command.contains(&format!("<{}>", r)))
+            {
+                writer.write_all(b"550 No such user\r\n").await.unwrap();
+            } else if upper.starts_with("RCPT TO:") {
+                accepted.push(
+                    command
+                        .split(['<', '>'])
+                        .nth(1)
+                        .unwrap_or_default()
+                        .to_string(),
+                );
+                writer.write_all(b"250 OK\r\n").await.unwrap();
+            } else {
+                writer.write_all(b"250 OK\r\n").await.unwrap();
+            }
+        }
+    }
+}
+
+/// 连接本地 `port` 上模拟服务端的配置，其余字段为默认值；测试在此基础上用结构体更新语法修改
+pub(crate) fn test_config(port: u16) -> Config {
+    Config {
+        smtp_server: "127.0.0.1".to_string(),
+        port,
+        ..Config::default()
+    }
+}
+
+pub(crate) fn write_test_emls(dir: &Path, count: usize) -> Vec<String> {
+    (0..count)
+        .map(|n| {
+            let path = dir.join(format!("{}.eml", n));
+            fs::write(&path, TEST_EML).unwrap();
+            path.to_string_lossy().to_string()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mailer::Mailer;
+
+    #[tokio::test]
+    async fn test_two_emls_arrive_intact() {
+        let dir = tempfile::tempdir().unwrap();
+        let second = "From: other@example.com\r\nTo: rcpt@example.com\r\nSubject: second\r\n\r\n..leading dots\r\nend\r\n";
+        fs::write(dir.path().join("a.eml"), TEST_EML).unwrap();
+        fs::write(dir.path().join("b.eml"), second).unwrap();
+        let server = MockSmtpServer::default();
+        let port = server.listen().await;
+        let config = Config {
+            dir: Some(dir.path().to_string_lossy().to_string()),
+            processes: "1".to_string(),
+            ..test_config(port)
+        };
+
+        let stats = Mailer::new(config).send_all().await.unwrap();
+
+        assert_eq!(stats.success_count(), 2);
+        let mut messages = server.messages.lock().unwrap().clone();
+        messages.sort();
+        assert_eq!(messages, [second.as_bytes().to_vec(), TEST_EML.as_bytes().to_vec()]);
+        assert_eq!(server.commands_starting_with("MAIL FROM:"), 2);
+    }
+}
@@ -0,0 +1,118 @@
+//! 不经中继直接投递到收件人域名的MX主机
+//!
+//! 开启 `direct_mx` 且未设置 `smtp_server` 时，EML 批量发送按收件人域名分组，
+//! 查询每个域名的MX记录后按优先级（数值越小越优先）依次连接各主机的 `port`
+//! （默认25），连接失败时换下一个主机，直到某个主机接受连接为止。域名没有MX记录时
+//! 按 RFC 5321 把域名本身作为唯一的MX主机。
+//!
+//! 查询通过 [`MxResolver`] 完成，默认使用系统DNS配置（[`DnsMxResolver`]），
+//! 测试或特殊环境可通过 `Mailer::with_mx_resolver` 替换。
+
+use anyhow::Result;
+use futures::future::BoxFuture;
+use mail_auth::common::cache::NoCache;
+use mail_auth::hickory_resolver::proto::op::ResponseCode;
+use mail_auth::{MessageAuthenticator, MX};
+use std::sync::Arc;
+
+/// 一条MX记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MxRecord {
+    pub preference: u16,
+    pub exchange: String,
+}
+
+/// 查询域名的MX记录；域名存在但没有MX记录时返回空列表
+pub trait MxResolver: Send + Sync {
+    fn lookup<'a>(&'a self, domain: &'a str) -> BoxFuture<'a, Result<Vec<MxRecord>>>;
+}
+
+/// 使用系统DNS配置（`/etc/resolv.conf` 等）查询MX记录
+pub struct DnsMxResolver(MessageAuthenticator);
+
+impl DnsMxResolver {
+    pub fn new() -> Result<Self> {
+        Ok(Self(MessageAuthenticator::new_system_conf()?))
+    }
+}
+
+impl MxResolver for DnsMxResolver {
+    fn lookup<'a>(&'a self, domain: &'a str) -> BoxFuture<'a, Result<Vec<MxRecord>>> {
+        Box::pin(async move {
+            let records = match self
+                .0
+                .mx_lookup(domain, None::<&NoCache<String, Arc<Vec<MX>>>>)
+                .await
+            {
+                Ok(records) => records,
+                // NOERROR 但没有MX记录：域名存在，按隐式MX处理
+                Err(mail_auth::Error::DnsRecordNotFound(ResponseCode::NoError)) => {
+                    return Ok(Vec::new())
+                }
+                Err(e) => return Err(e.into()),
+            };
+            Ok(records
+                .iter()
+                .flat_map(|mx| {
+                    mx.exchanges.iter().map(|exchange| MxRecord {
+                        preference: mx.preference,
+                        exchange: exchange.clone(),
+                    })
+                })
+                .collect())
+        })
+    }
+}
+
+/// 按优先级排序的MX主机名（去掉末尾的点）；没有MX记录时返回域名本身
+pub async fn mx_hosts(resolver: &dyn MxResolver, domain: &str) -> Result<Vec<String>> {
+    let mut records = resolver.lookup(domain).await?;
+    if records.is_empty() {
+        return Ok(vec![domain.to_string()]);
+    }
+    // 稳定排序：同一优先级的主机保持解析器返回的顺序
+    records.sort_by_key(|record| record.preference);
+    Ok(records
+        .into_iter()
+        .map(|record| record.exchange.trim_end_matches('.').to_string())
+        // 空主机名（RFC 7505 的 `0 .`）表示该域名不接收邮件
+        .filter(|host| !host.is_empty())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct StaticMx(Vec<MxRecord>);
+
+    impl MxResolver for StaticMx {
+        fn lookup<'a>(&'a self, _: &'a str) -> BoxFuture<'a, Result<Vec<MxRecord>>> {
+            Box::pin(async move { Ok(self.0.clone()) })
+        }
+    }
+
+    fn record(preference: u16, exchange: &str) -> MxRecord {
+        MxRecord {
+            preference,
+            exchange: exchange.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hosts_sorted_by_preference_with_implicit_fallback() {
+        let resolver = StaticMx(vec![
+            record(20, "backup.example.com."),
+            record(10, "mx1.example.com."),
+            record(10, "mx2.example.com"),
+        ]);
+        assert_eq!(
+            mx_hosts(&resolver, "example.com").await.unwrap(),
+            ["mx1.example.com", "mx2.example.com", "backup.example.com"]
+        );
+        assert_eq!(
+            mx_hosts(&StaticMx(Vec::new()), "example.com").await.unwrap(),
+            ["example.com"]
+        );
+    }
+}
@@ -0,0 +1,136 @@
+//! 发送进度回调
+//!
+//! 库的使用方（CLI、GUI）实现 [`SendProgress`] 并通过 `Mailer::new_with_progress`
+//! 注册，即可在每封邮件发送完成或失败、每个批次开始时直接收到事件，无需解析日志
+//! 或轮询统计数据。回调在发送任务中同步调用，应尽快返回。
+
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 一封邮件的一次投递尝试的结果，见 [`SendProgress::on_delivery`]
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct Delivery<'a> {
+    /// 源文件路径
+    pub path: &'a str,
+    /// 信封发件人；在解析邮件之前失败（如连接失败、文件无法读取）时为 None
+    pub envelope_from: Option<&'a str>,
+    /// 信封收件人
+    pub recipients: &'a [String],
+    /// 是否发送成功
+    pub sent: bool,
+    /// 服务器对该邮件的最后一条应答；没有应答时为失败原因，成功且无应答时为空
+    pub response: &'a str,
+    /// 失败发生的阶段，发送成功时为 None
+    pub failure: Option<FailureKind>,
+}
+
+/// 一封邮件失败的阶段（与按服务器应答划分的 `FailureClass` 无关）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    /// EML内容无法解析为邮件，未进行任何SMTP交互
+    Parse,
+    /// 读取文件、建立连接或SMTP事务中的失败
+    Send,
+}
+
+/// 按邮件粒度的发送进度事件，所有方法默认不做任何处理
+pub trait SendProgress: Send + Sync {
+    /// 一封邮件发送成功，`duration` 为SMTP事务耗时
+    fn on_email_sent(&self, path: &str, duration: Duration) {
+        let _ = (path, duration);
+    }
+
+    /// 一封邮件发送失败，`error` 为计入统计的错误描述
+    fn on_email_failed(&self, path: &str, error: &str) {
+        let _ = (path, error);
+    }
+
+    /// 进程组 `group`（从1开始）开始发送一个包含 `size` 封邮件的批次
+    fn on_batch_start(&self, group: usize, size: usize) {
+        let _ = (group, size);
+    }
+
+    /// EML 批量发送中每封邮件的投递尝试结束时调用（在 `on_email_sent`/`on_email_failed`
+    /// 之外），附带信封与服务器应答；演练模式下只对构建失败的邮件调用
+    fn on_delivery(&self, delivery: &Delivery<'_>) {
+        let _ = delivery;
+    }
+
+    /// EML 批量发送期间定期报告：`qps` 为最近一个报告间隔内的每秒处理邮件数，
+    /// `eta` 为预计剩余时间（尚无法估算时为 None）
+    fn on_throughput(&self, qps: f64, eta: Option<Duration>) {
+        let _ = (qps, eta);
+    }
+}
+
+/// 挂入进度回调链的内部功能（检查点、投递清单、钩子、熔断与停止策略）对事件的处理，
+/// 由 [`ObservedProgress`] 在转发给内层回调之前调用；所有方法默认不做任何处理
+pub(crate) trait ProgressObserver: Send + Sync {
+    fn email_sent(&self, path: &str) {
+        let _ = path;
+    }
+
+    fn email_failed(&self, path: &str, error: &str) {
+        let _ = (path, error);
+    }
+
+    fn delivery(&self, delivery: &Delivery<'_>) {
+        let _ = delivery;
+    }
+}
+
+impl<T: ProgressObserver> ProgressObserver for Arc<T> {
+    fn email_sent(&self, path: &str) {
+        (**self).email_sent(path);
+    }
+
+    fn email_failed(&self, path: &str, error: &str) {
+        (**self).email_failed(path, error);
+    }
+
+    fn delivery(&self, delivery: &Delivery<'_>) {
+        (**self).delivery(delivery);
+    }
+}
+
+/// 先把事件交给 `observer`，再原样转发给内层进度回调
+pub(crate) struct ObservedProgress<O> {
+    pub observer: O,
+    pub inner: Option<Arc<dyn SendProgress>>,
+}
+
+impl<O: ProgressObserver> SendProgress for ObservedProgress<O> {
+    fn on_email_sent(&self, path: &str, duration: Duration) {
+        self.observer.email_sent(path);
+        if let Some(inner) = &self.inner {
+            inner.on_email_sent(path, duration);
+        }
+    }
+
+    fn on_email_failed(&self, path: &str, error: &str) {
+        self.observer.email_failed(path, error);
+        if let Some(inner) = &self.inner {
+            inner.on_email_failed(path, error);
+        }
+    }
+
+    fn on_batch_start(&self, group: usize, size: usize) {
+        if let Some(inner) = &self.inner {
+            inner.on_batch_start(group, size);
+        }
+    }
+
+    fn on_delivery(&self, delivery: &Delivery<'_>) {
+        self.observer.delivery(delivery);
+        if let Some(inner) = &self.inner {
+            inner.on_delivery(delivery);
+        }
+    }
+
+    fn on_throughput(&self, qps: f64, eta: Option<Duration>) {
+        if let Some(inner) = &self.inner {
+            inner.on_throughput(qps, eta);
+        }
+    }
+}
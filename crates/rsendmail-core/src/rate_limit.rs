@@ -0,0 +1,165 @@
+//! 按每秒邮件数限制发送速率
+//!
+//! 采用容量为1的令牌桶：每隔 `1 / rate` 秒产生一个发送许可，不允许突发。
+//! 所有发送任务共享同一个限速器（克隆后指向同一状态），因此无论启动多少个
+//! 进程组，整体发送速率都不会超过上限；与 `email_send_interval_ms` 不同，
+//! 发送本身的耗时计入间隔，实际吞吐量不会随发送耗时漂移。
+//!
+//! 设置 `warmup_seconds` 后，从第一次获取许可开始的预热期内，速率从
+//! `warmup_start_rate` 线性增加到 `max_rate_per_second`，用于新发送IP的信誉预热。
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::config::Config;
+
+/// 预热期的线性速率爬升
+#[derive(Clone, Copy)]
+struct Warmup {
+    duration: Duration,
+    start_rate: f64,
+}
+
+#[derive(Default)]
+struct Slots {
+    /// 第一个许可的发放时间，预热期从此开始计算
+    started: Option<Instant>,
+    /// 下一个可用发送许可的时间点
+    next: Option<Instant>,
+}
+
+#[derive(Clone)]
+pub struct RateLimiter {
+    rate: f64,
+    warmup: Option<Warmup>,
+    slots: Arc<Mutex<Slots>>,
+}
+
+impl RateLimiter {
+    /// 创建每秒最多发放 `per_second` 个许可的限速器，速率无效（非正数或非有限值）时返回 None
+    pub fn new(per_second: f64) -> Option<Self> {
+        if !per_second.is_finite() || per_second <= 0.0 {
+            return None;
+        }
+        Some(Self {
+            rate: per_second,
+            warmup: None,
+            slots: Arc::new(Mutex::new(Slots::default())),
+        })
+    }
+
+    /// 在 `duration` 内从 `start_rate` 线性爬升到完整速率；预热时长为0、
+    /// 起始速率无效或不低于完整速率时不做预热
+    pub fn with_warmup(mut self, duration: Duration, start_rate: f64) -> Self {
+        self.warmup = (!duration.is_zero()
+            && start_rate.is_finite()
+            && start_rate > 0.0
+            && start_rate < self.rate)
+            .then_some(Warmup {
+                duration,
+                start_rate,
+            });
+        self
+    }
+
+    /// 根据 `max_rate_per_second` 与预热配置创建限速器，未设置速率上限时返回 None
+    pub fn from_config(config: &Config) -> Option<Self> {
+        config.max_rate_per_second.and_then(Self::new).map(|limiter| {
+            limiter.with_warmup(
+                Duration::from_secs(config.warmup_seconds),
+                config.warmup_start_rate,
+            )
+        })
+    }
+
+    /// 距第一个许可 `elapsed` 时的许可间隔
+    fn interval_at(&self, elapsed: Duration) -> Duration {
+        let rate = match self.warmup {
+            Some(warmup) if elapsed < warmup.duration => {
+                let progress = elapsed.as_secs_f64() / warmup.duration.as_secs_f64();
+                warmup.start_rate + (self.rate - warmup.start_rate) * progress
+            }
+            _ => self.rate,
+        };
+        Duration::from_secs_f64(1.0 / rate)
+    }
+
+    /// 从第一个许可起发放 `permits` 个许可所需的时间（含预热期）
+    pub fn duration_for(&self, permits: usize) -> Duration {
+        let mut elapsed = Duration::ZERO;
+        for _ in 1..permits {
+            elapsed += self.interval_at(elapsed);
+        }
+        elapsed
+    }
+
+    /// 等待直到获得下一个发送许可
+    pub async fn acquire(&self) {
+        let slot = {
+            let mut slots = self.slots.lock().unwrap();
+            let now = Instant::now();
+            // 空闲期间不累积许可，保证任意时间窗口内都不超过上限
+            let slot = slots.next.map_or(now, |next| next.max(now));
+            let started = *slots.started.get_or_insert(slot);
+            slots.next = Some(slot + self.interval_at(slot - started));
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_invalid_rates() {
+        assert!(RateLimiter::new(0.0).is_none());
+        assert!(RateLimiter::new(-1.0).is_none());
+        assert!(RateLimiter::new(f64::NAN).is_none());
+        assert!(RateLimiter::new(f64::INFINITY).is_none());
+        assert_eq!(
+            RateLimiter::new(4.0).unwrap().interval_at(Duration::ZERO),
+            Duration::from_millis(250)
+        );
+    }
+
+    #[test]
+    fn test_warmup_interpolates_linearly() {
+        let limiter = RateLimiter::new(40.0)
+            .unwrap()
+            .with_warmup(Duration::from_secs(4), 10.0);
+        assert_eq!(limiter.interval_at(Duration::ZERO), Duration::from_millis(100));
+        assert_eq!(limiter.interval_at(Duration::from_secs(2)), Duration::from_secs_f64(1.0 / 25.0));
+        assert_eq!(limiter.interval_at(Duration::from_secs(5)), Duration::from_millis(25));
+        // 预热时长为0时不做预热
+        let limiter = RateLimiter::new(40.0).unwrap().with_warmup(Duration::ZERO, 10.0);
+        assert_eq!(limiter.interval_at(Duration::ZERO), Duration::from_millis(25));
+        assert_eq!(limiter.duration_for(0), Duration::ZERO);
+        assert_eq!(limiter.duration_for(41), Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_first_warmup_quarter_runs_at_about_a_quarter_rate() {
+        // 起始速率为完整速率的1/8时，前1/4预热期内的平均速率约为完整速率的 1/8 + 7/8 × 1/8 ≈ 1/4
+        let full_rate = 40.0;
+        let limiter = RateLimiter::new(full_rate)
+            .unwrap()
+            .with_warmup(Duration::from_secs(2), full_rate / 8.0);
+        let window = Duration::from_millis(500);
+
+        let start = Instant::now();
+        let mut permits = 0;
+        while start.elapsed() < window {
+            limiter.acquire().await;
+            if start.elapsed() < window {
+                permits += 1;
+            }
+        }
+
+        let full = full_rate * window.as_secs_f64();
+        let measured = permits as f64 / full;
+        assert!((0.15..=0.4).contains(&measured), "permits={} of {}", permits, full);
+    }
+}
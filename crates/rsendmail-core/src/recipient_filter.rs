@@ -0,0 +1,118 @@
+//! 按收件人域名过滤信封收件人
+//!
+//! 配置了 `recipient_allow_domains` 时只保留域名在允许列表中的收件人，配置了
+//! `recipient_block_domains` 时丢弃域名在阻止列表中的收件人，两者同时配置时阻止列表优先。
+//! 域名不区分大小写，规则同时匹配其子域名（`example.com` 匹配 `mail.example.com`）。
+//! 用于在生产数据上测试时保证邮件不会发往真实的外部地址。
+
+use log::warn;
+
+use crate::config::Config;
+
+/// 收件人域名的允许与阻止规则
+#[derive(Debug)]
+pub struct RecipientFilter {
+    allow: Option<Vec<String>>,
+    block: Vec<String>,
+}
+
+impl RecipientFilter {
+    /// 两个列表都未配置时返回 None
+    pub fn from_config(config: &Config) -> Option<Self> {
+        if config.recipient_allow_domains.is_none() && config.recipient_block_domains.is_none() {
+            return None;
+        }
+        let normalize = |domains: &Vec<String>| -> Vec<String> {
+            domains
+                .iter()
+                .map(|domain| domain.trim().trim_start_matches(['@', '.']).to_ascii_lowercase())
+                .filter(|domain| !domain.is_empty())
+                .collect()
+        };
+        Some(Self {
+            allow: config.recipient_allow_domains.as_ref().map(normalize),
+            block: config.recipient_block_domains.as_ref().map(normalize).unwrap_or_default(),
+        })
+    }
+
+    /// 是否允许向该地址投递；配置了允许列表时没有域名的地址一律不允许
+    pub fn allows(&self, address: &str) -> bool {
+        let domain = address
+            .rsplit_once('@')
+            .map(|(_, domain)| domain.trim_end_matches('>').to_ascii_lowercase());
+        let matches = |rules: &[String]| {
+            domain.as_deref().is_some_and(|domain| {
+                rules.iter().any(|rule| {
+                    domain == rule
+                        || domain
+                            .strip_suffix(rule.as_str())
+                            .is_some_and(|prefix| prefix.ends_with('.'))
+                })
+            })
+        };
+        if matches(&self.block) {
+            return false;
+        }
+        self.allow.as_deref().is_none_or(matches)
+    }
+
+    /// 移除不允许的收件人并逐个记录警告，返回移除的数量
+    pub fn retain(&self, recipients: &mut Vec<String>, label: &str) -> usize {
+        let before = recipients.len();
+        recipients.retain(|recipient| {
+            let allowed = self.allows(recipient);
+            if !allowed {
+                warn!("收件人 {} 被收件人域名过滤规则排除: {}", recipient, label);
+            }
+            allowed
+        });
+        before - recipients.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(allow: Option<&[&str]>, block: Option<&[&str]>) -> RecipientFilter {
+        let list = |domains: &[&str]| domains.iter().map(|d| d.to_string()).collect();
+        RecipientFilter::from_config(&Config {
+            recipient_allow_domains: allow.map(list),
+            recipient_block_domains: block.map(list),
+            ..Config::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_allow_only_keeps_listed_domains_and_subdomains() {
+        let filter = filter(Some(&["Example.com", "@test.org"]), None);
+        assert!(filter.allows("a@example.com"));
+        assert!(filter.allows("b@mail.EXAMPLE.com"));
+        assert!(filter.allows("c@test.org"));
+        assert!(!filter.allows("d@notexample.com"));
+        assert!(!filter.allows("e@gmail.com"));
+        assert!(!filter.allows("postmaster"));
+        assert!(RecipientFilter::from_config(&Config::default()).is_none());
+    }
+
+    #[test]
+    fn test_block_only_drops_listed_domains() {
+        let filter = filter(None, Some(&["gmail.com"]));
+        let mut recipients = vec![
+            "a@gmail.com".to_string(),
+            "b@example.com".to_string(),
+            "c@eu.gmail.com".to_string(),
+        ];
+        assert_eq!(filter.retain(&mut recipients, "test.eml"), 2);
+        assert_eq!(recipients, ["b@example.com"]);
+    }
+
+    #[test]
+    fn test_block_list_wins_over_allow_list() {
+        let filter = filter(Some(&["example.com"]), Some(&["vip.example.com"]));
+        assert!(filter.allows("a@example.com"));
+        assert!(!filter.allows("b@vip.example.com"));
+        assert!(!filter.allows("c@other.com"));
+    }
+}
@@ -0,0 +1,231 @@
+//! 多个SMTP中继之间的加权轮询与故障转移
+//!
+//! 配置了 `relays` 时，EML 批量发送的各进程组在每次建立连接时从中继池中按权重
+//! 轮流选择中继（平滑加权轮询，权重 3:1 的两个中继按 a a b a 的顺序交替，而不是
+//! 连续选中同一个）。连接失败或收到 421 等要求断开连接的应答时，该中继在
+//! `relay_cooldown_secs` 内被跳过；所有中继都处于冷却期时仍按原顺序选择，
+//! 不会因此停止发送。所有进程组共享同一个中继池（克隆后指向同一状态）。
+//!
+//! 设置 `per_host_connections` 后，[`HostSlots`] 限制每个端点同时打开的连接数：
+//! 选中的中继已满时换下一个中继，所有中继都满时等待。
+
+use anyhow::{anyhow, Result};
+use rsendmail_i18n::tr_with_args;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::config::Config;
+
+/// 一个中继端点，格式为 `host[:port][*weight]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relay {
+    pub host: String,
+    pub port: u16,
+    pub weight: usize,
+}
+
+impl Relay {
+    /// 解析 `host[:port][*weight]`，未指定端口时使用 `default_port`，未指定权重时为1；
+    /// 格式无效时返回 None
+    pub fn parse(spec: &str, default_port: u16) -> Option<Self> {
+        let spec = spec.trim();
+        let (endpoint, weight) = match spec.split_once('*') {
+            Some((endpoint, weight)) => (
+                endpoint,
+                weight.trim().parse::<usize>().ok().filter(|w| *w > 0)?,
+            ),
+            None => (spec, 1),
+        };
+        let (host, port) = match endpoint.rsplit_once(':') {
+            Some((host, port)) => (host, port.trim().parse::<u16>().ok()?),
+            None => (endpoint, default_port),
+        };
+        let host = host.trim();
+        if host.is_empty() {
+            return None;
+        }
+        Some(Self {
+            host: host.to_string(),
+            port,
+            weight,
+        })
+    }
+
+    /// 以该中继替换 `smtp_server` 与 `port` 后的配置
+    pub fn apply(&self, config: &Config) -> Config {
+        Config {
+            smtp_server: self.host.clone(),
+            port: self.port,
+            ..config.clone()
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RelayPool {
+    relays: Arc<Vec<Relay>>,
+    /// 按权重展开的选择顺序（中继下标）
+    schedule: Arc<Vec<usize>>,
+    cursor: Arc<AtomicUsize>,
+    /// 每个中继冷却期结束的时间点
+    unhealthy_until: Arc<Mutex<Vec<Option<Instant>>>>,
+    cooldown: Duration,
+}
+
+impl RelayPool {
+    pub fn new(relays: Vec<Relay>, cooldown: Duration) -> Option<Self> {
+        if relays.is_empty() {
+            return None;
+        }
+        let schedule = smooth_weighted_schedule(&relays);
+        Some(Self {
+            unhealthy_until: Arc::new(Mutex::new(vec![None; relays.len()])),
+            relays: Arc::new(relays),
+            schedule: Arc::new(schedule),
+            cursor: Arc::new(AtomicUsize::new(0)),
+            cooldown,
+        })
+    }
+
+    /// 根据 `relays` 创建中继池，未配置时返回 None（使用 `smtp_server`）
+    pub fn from_config(config: &Config) -> Result<Option<Self>> {
+        let relays = config
+            .relays
+            .iter()
+            .map(|spec| {
+                Relay::parse(spec, config.port).ok_or_else(|| {
+                    anyhow!(tr_with_args("core.mailer.invalid_relay", &[("relay", spec)]))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self::new(relays, Duration::from_secs(config.relay_cooldown_secs)))
+    }
+
+    /// 不同中继的数量
+    pub fn len(&self) -> usize {
+        self.relays.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.relays.is_empty()
+    }
+
+    /// 按加权轮询顺序选择下一个不在冷却期的中继；全部处于冷却期时不跳过
+    pub fn next(&self) -> Relay {
+        let now = Instant::now();
+        let unhealthy_until = self.unhealthy_until.lock().unwrap();
+        let start = self.cursor.fetch_add(1, Ordering::Relaxed);
+        let pick = (0..self.schedule.len())
+            .map(|offset| self.schedule[(start + offset) % self.schedule.len()])
+            .find(|&index| unhealthy_until[index].is_none_or(|until| until <= now))
+            .unwrap_or(self.schedule[start % self.schedule.len()]);
+        self.relays[pick].clone()
+    }
+
+    /// 将中继标记为暂时不可用，冷却期内 `next` 会跳过它
+    pub fn mark_unhealthy(&self, relay: &Relay) {
+        if let Some(index) = self.relays.iter().position(|r| r == relay) {
+            self.unhealthy_until.lock().unwrap()[index] = Some(Instant::now() + self.cooldown);
+        }
+    }
+}
+
+type HostKey = (String, u16);
+
+/// 每个端点（`host:port`）各自的连接名额，所有进程组共享（克隆后指向同一状态）
+#[derive(Clone)]
+pub struct HostSlots {
+    limit: usize,
+    slots: Arc<Mutex<HashMap<HostKey, Arc<Semaphore>>>>,
+}
+
+impl HostSlots {
+    /// 根据 `per_host_connections` 创建，未配置时返回 None
+    pub fn from_config(config: &Config) -> Option<Self> {
+        config.per_host_connections.map(|limit| Self {
+            limit: limit.max(1),
+            slots: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    fn semaphore(&self, host: &str, port: u16) -> Arc<Semaphore> {
+        self.slots
+            .lock()
+            .unwrap()
+            .entry((host.to_ascii_lowercase(), port))
+            .or_insert_with(|| Arc::new(Semaphore::new(self.limit)))
+            .clone()
+    }
+
+    /// 端点还有空闲名额时立即取得一个，否则返回 None
+    pub fn try_acquire(&self, host: &str, port: u16) -> Option<OwnedSemaphorePermit> {
+        self.semaphore(host, port).try_acquire_owned().ok()
+    }
+
+    /// 等待端点的空闲名额，名额随返回值释放
+    pub async fn acquire(&self, host: &str, port: u16) -> OwnedSemaphorePermit {
+        self.semaphore(host, port)
+            .acquire_owned()
+            .await
+            .expect("host semaphore is never closed")
+    }
+}
+
+/// 平滑加权轮询：每轮每个中继的当前值加上其权重，选出当前值最大的中继后减去权重总和
+fn smooth_weighted_schedule(relays: &[Relay]) -> Vec<usize> {
+    let total: usize = relays.iter().map(|r| r.weight).sum();
+    let mut current = vec![0isize; relays.len()];
+    (0..total)
+        .map(|_| {
+            for (value, relay) in current.iter_mut().zip(relays) {
+                *value += relay.weight as isize;
+            }
+            let (pick, _) = current
+                .iter()
+                .enumerate()
+                .max_by_key(|&(index, value)| (*value, std::cmp::Reverse(index)))
+                .unwrap();
+            current[pick] -= total as isize;
+            pick
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_relay_specs() {
+        assert_eq!(
+            Relay::parse("mx1.example.com:2525*3", 25).unwrap(),
+            Relay {
+                host: "mx1.example.com".to_string(),
+                port: 2525,
+                weight: 3
+            }
+        );
+        assert_eq!(Relay::parse("mx2.example.com", 587).unwrap().port, 587);
+        assert!(Relay::parse("mx.example.com:abc", 25).is_none());
+        assert!(Relay::parse("mx.example.com*0", 25).is_none());
+        assert!(Relay::parse(":25", 25).is_none());
+    }
+
+    #[test]
+    fn test_weighted_rotation_skips_unhealthy_relays() {
+        let a = Relay::parse("a:25*3", 25).unwrap();
+        let b = Relay::parse("b:25", 25).unwrap();
+        let pool = RelayPool::new(vec![a.clone(), b.clone()], Duration::from_secs(60)).unwrap();
+        let picks: Vec<String> = (0..8).map(|_| pool.next().host).collect();
+        assert_eq!(picks, ["a", "a", "b", "a", "a", "a", "b", "a"]);
+
+        pool.mark_unhealthy(&a);
+        assert!((0..4).all(|_| pool.next() == b));
+        // 全部处于冷却期时仍然返回中继
+        pool.mark_unhealthy(&b);
+        pool.next();
+    }
+}
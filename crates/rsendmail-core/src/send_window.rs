@@ -0,0 +1,88 @@
+//! 每天允许发送的时间窗口（本地时间）
+//!
+//! `send_window` 形如 `08:00-18:00`，每轮发送开始前检查当前时间，不在窗口内时等到
+//! 窗口重新打开。结束时间早于开始时间表示跨越午夜（如 `22:00-06:00`），开始与结束
+//! 相同表示全天都可发送。
+
+use chrono::{NaiveTime, TimeDelta};
+use std::time::Duration;
+
+/// 已解析的发送时间窗口，包含开始时间、不包含结束时间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SendWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl SendWindow {
+    /// 解析 `HH:MM-HH:MM` 形式的窗口，格式不正确时返回 None
+    pub fn parse(window: &str) -> Option<Self> {
+        let (start, end) = window.split_once('-')?;
+        let time = |value: &str| NaiveTime::parse_from_str(value.trim(), "%H:%M").ok();
+        Some(Self {
+            start: time(start)?,
+            end: time(end)?,
+        })
+    }
+
+    /// 距窗口重新打开还需等待的时间，`now` 在窗口内时为零
+    pub fn wait_from(&self, now: NaiveTime) -> Duration {
+        if in_window(now, self.start, self.end) {
+            return Duration::ZERO;
+        }
+        let mut wait = self.start.signed_duration_since(now);
+        if wait < TimeDelta::zero() {
+            wait += TimeDelta::days(1);
+        }
+        wait.to_std().unwrap_or_default()
+    }
+}
+
+/// `now` 是否位于 `[start, end)` 内；`end` 早于 `start` 时窗口跨越午夜，两者相同时为全天
+pub fn in_window(now: NaiveTime, start: NaiveTime, end: NaiveTime) -> bool {
+    if start == end {
+        true
+    } else if start < end {
+        start <= now && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_in_window_same_day_and_past_midnight() {
+        assert!(in_window(at(8, 0), at(8, 0), at(18, 0)));
+        assert!(in_window(at(12, 30), at(8, 0), at(18, 0)));
+        assert!(!in_window(at(18, 0), at(8, 0), at(18, 0)));
+        assert!(!in_window(at(7, 59), at(8, 0), at(18, 0)));
+
+        // 22:00-06:00 跨越午夜
+        assert!(in_window(at(23, 0), at(22, 0), at(6, 0)));
+        assert!(in_window(at(0, 0), at(22, 0), at(6, 0)));
+        assert!(in_window(at(5, 59), at(22, 0), at(6, 0)));
+        assert!(!in_window(at(6, 0), at(22, 0), at(6, 0)));
+        assert!(!in_window(at(12, 0), at(22, 0), at(6, 0)));
+
+        assert!(in_window(at(3, 0), at(9, 0), at(9, 0)));
+    }
+
+    #[test]
+    fn test_wait_until_window_reopens() {
+        let window = SendWindow::parse("08:00-18:00").unwrap();
+        assert_eq!(window.wait_from(at(9, 0)), Duration::ZERO);
+        assert_eq!(window.wait_from(at(7, 30)), Duration::from_secs(30 * 60));
+        assert_eq!(window.wait_from(at(19, 0)), Duration::from_secs(13 * 3600));
+
+        assert_eq!(SendWindow::parse("22:00 - 06:00").unwrap().end, at(6, 0));
+        assert_eq!(SendWindow::parse("8-18"), None);
+        assert_eq!(SendWindow::parse("08:00"), None);
+    }
+}
@@ -0,0 +1,234 @@
+//! 发送任务的运行状态控制（两阶段停止）
+//!
+//! 第一次停止请求进入排空状态：正在发送的邮件继续完成并正常QUIT，但不再开始
+//! 新的邮件；第二次停止请求进入中止状态：正在进行的SMTP事务被立即放弃，连接
+//! 直接断开。
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+/// 发送任务的运行状态
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunState {
+    /// 正常发送
+    Running = 0,
+    /// 完成当前邮件后停止
+    Draining = 1,
+    /// 立即中止
+    Aborting = 2,
+}
+
+impl RunState {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => RunState::Running,
+            1 => RunState::Draining,
+            _ => RunState::Aborting,
+        }
+    }
+}
+
+struct Inner {
+    state: AtomicU8,
+    changed: Notify,
+}
+
+/// 在CLI/GUI与各发送任务之间共享的运行状态句柄，克隆后指向同一状态
+#[derive(Clone)]
+pub struct RunControl {
+    inner: Arc<Inner>,
+}
+
+impl Default for RunControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RunControl {
+    /// 创建处于 Running 状态的控制句柄
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                state: AtomicU8::new(RunState::Running as u8),
+                changed: Notify::new(),
+            }),
+        }
+    }
+
+    pub fn state(&self) -> RunState {
+        RunState::from_u8(self.inner.state.load(Ordering::SeqCst))
+    }
+
+    /// 是否允许开始新的邮件或批次
+    pub fn is_running(&self) -> bool {
+        self.state() == RunState::Running
+    }
+
+    pub fn is_aborting(&self) -> bool {
+        self.state() == RunState::Aborting
+    }
+
+    /// 推进到下一个停止阶段（Running → Draining → Aborting），返回新的状态
+    pub fn request_stop(&self) -> RunState {
+        let previous = self
+            .inner
+            .state
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |state| {
+                Some((state + 1).min(RunState::Aborting as u8))
+            })
+            .unwrap_or_else(|state| state);
+        self.inner.changed.notify_waiters();
+        RunState::from_u8((previous + 1).min(RunState::Aborting as u8))
+    }
+
+    /// 仅在 Running 状态时切换到 Draining，已在停止中则保持不变
+    pub fn drain(&self) {
+        let _ = self.inner.state.compare_exchange(
+            RunState::Running as u8,
+            RunState::Draining as u8,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        );
+        self.inner.changed.notify_waiters();
+    }
+
+    /// 立即进入 Aborting 状态
+    pub fn abort(&self) {
+        self.inner
+            .state
+            .store(RunState::Aborting as u8, Ordering::SeqCst);
+        self.inner.changed.notify_waiters();
+    }
+
+    /// 恢复为 Running 状态，用于开始新一次发送
+    pub fn reset(&self) {
+        self.inner
+            .state
+            .store(RunState::Running as u8, Ordering::SeqCst);
+        self.inner.changed.notify_waiters();
+    }
+
+    /// 等待离开 Running 状态，用于提前结束间隔休眠
+    pub async fn stopped(&self) {
+        self.wait_until(|state| state != RunState::Running).await
+    }
+
+    /// 等待进入 Aborting 状态，用于放弃正在进行的SMTP事务
+    pub async fn aborted(&self) {
+        self.wait_until(|state| state == RunState::Aborting).await
+    }
+
+    /// 执行 `fut`，若期间进入 Aborting 状态则放弃它并返回 None
+    pub async fn unless_aborted<F: Future>(&self, fut: F) -> Option<F::Output> {
+        tokio::select! {
+            // 中止优先：两者同时就绪时不再记录已放弃事务的结果
+            biased;
+            _ = self.aborted() => None,
+            output = fut => Some(output),
+        }
+    }
+
+    /// `limit` 到期时若仍在运行则调用 `on_expire` 并切换到排空状态；
+    /// 返回的计时器被丢弃时取消，提前停止时计时器同样结束
+    pub fn drain_after(
+        &self,
+        limit: Duration,
+        on_expire: impl FnOnce() + Send + 'static,
+    ) -> DrainTimer {
+        let control = self.clone();
+        DrainTimer {
+            task: tokio::spawn(async move {
+                tokio::select! {
+                    _ = control.stopped() => {}
+                    _ = tokio::time::sleep(limit) => {
+                        on_expire();
+                        control.drain();
+                    }
+                }
+            }),
+        }
+    }
+
+    async fn wait_until(&self, done: impl Fn(RunState) -> bool) {
+        loop {
+            // 先注册等待再检查状态，避免错过两者之间的状态变化
+            let notified = self.inner.changed.notified();
+            if done(self.state()) {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// [`RunControl::drain_after`] 返回的计时器
+pub struct DrainTimer {
+    task: JoinHandle<()>,
+}
+
+impl Drop for DrainTimer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_stop_advances_through_states() {
+        let control = RunControl::new();
+        assert!(control.is_running());
+        assert_eq!(control.request_stop(), RunState::Draining);
+        assert!(!control.is_running());
+        assert!(!control.is_aborting());
+        assert_eq!(control.request_stop(), RunState::Aborting);
+        assert_eq!(control.request_stop(), RunState::Aborting);
+
+        control.reset();
+        control.drain();
+        control.drain();
+        assert_eq!(control.state(), RunState::Draining);
+    }
+
+    #[tokio::test]
+    async fn test_aborted_wakes_only_on_second_stop() {
+        let control = RunControl::new();
+        let waiter = tokio::spawn({
+            let control = control.clone();
+            async move { control.aborted().await }
+        });
+
+        control.request_stop();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiter.is_finished());
+
+        control.request_stop();
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_drain_after_drains_only_while_timer_is_held() {
+        let control = RunControl::new();
+        let dropped = control.drain_after(Duration::from_millis(20), || {});
+        drop(dropped);
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(control.is_running());
+
+        let _timer = control.drain_after(Duration::from_millis(20), || {});
+        tokio::time::timeout(Duration::from_secs(1), control.stopped())
+            .await
+            .unwrap();
+        assert_eq!(control.state(), RunState::Draining);
+    }
+}
@@ -1,18 +1,142 @@
-use rsendmail_i18n::{tr, tr_with_args};
+use regex::Regex;
+use rsendmail_i18n::{tr, tr_plural, tr_with_args};
+use serde::{Serialize, Serializer};
+use serde_json::{json, Value};
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt;
+use std::io::Write;
+use std::sync::OnceLock;
 use std::time::Duration;
 
+/// 按 RFC 4180 转义CSV字段：包含逗号、引号或换行时整体加引号，引号双写
+fn csv_field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// 发送失败的分类：4xx 应答为临时性（软）失败，5xx 应答为永久性（硬）失败，
+/// 没有SMTP应答码的错误（连接、超时、本地错误等）为未知
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureClass {
+    Soft,
+    Hard,
+    Unknown,
+}
+
+impl FailureClass {
+    /// 按错误描述中的第一个SMTP应答码分类，兼容 "550 5.1.1 ..." 与
+    /// "Code: 550, Enhanced code: ..." 两种格式
+    pub fn classify(error: &str) -> Self {
+        static CODE: OnceLock<Regex> = OnceLock::new();
+        let code =
+            CODE.get_or_init(|| Regex::new(r"(?:^|[\s:：,(])([45])\d{2}(?:[\s,.-]|$)").unwrap());
+        match code
+            .captures(error)
+            .map(|caps| caps.get(1).unwrap().as_str())
+        {
+            Some("4") => FailureClass::Soft,
+            Some(_) => FailureClass::Hard,
+            None => FailureClass::Unknown,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FailureClass::Soft => "soft",
+            FailureClass::Hard => "hard",
+            FailureClass::Unknown => "unknown",
+        }
+    }
+}
+
+/// 一组耗时的 p50/p90/p99（最近秩法）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Percentiles {
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+}
+
+impl Percentiles {
+    /// 排序一次后取各百分位，没有数据时返回 None
+    pub fn of(durations: &[Duration]) -> Option<Self> {
+        if durations.is_empty() {
+            return None;
+        }
+        let mut sorted = durations.to_vec();
+        sorted.sort_unstable();
+        let at = |percent: usize| sorted[(sorted.len() * percent).div_ceil(100).max(1) - 1];
+        Some(Self {
+            p50: at(50),
+            p90: at(90),
+            p99: at(99),
+        })
+    }
+
+    fn to_json(self) -> Value {
+        let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        json!({ "p50": ms(self.p50), "p90": ms(self.p90), "p99": ms(self.p99) })
+    }
+
+    /// 按 `key` 输出一行以毫秒为单位的百分位
+    fn write_line(self, f: &mut fmt::Formatter<'_>, key: &str) -> fmt::Result {
+        let ms = |duration: Duration| format!("{:.1}", duration.as_secs_f64() * 1000.0);
+        writeln!(
+            f,
+            "{}",
+            tr_with_args(
+                key,
+                &[("p50", &ms(self.p50)), ("p90", &ms(self.p90)), ("p99", &ms(self.p99))]
+            )
+        )
+    }
+}
+
 #[derive(Default)]
 pub struct Stats {
+    /// 成功发送的邮件数量（失败的邮件计入 `send_errors` / `parse_errors`，不计入此项）
     pub email_count: usize,
     pub parse_durations: Vec<Duration>,
     pub send_durations: Vec<Duration>,
     pub total_duration: Duration,
     pub parse_errors: usize,
     pub send_errors: usize,
+    /// 发送错误中服务器返回4xx（临时性失败）的次数
+    pub soft_failures: usize,
+    /// 发送错误中服务器返回5xx（永久性失败）的次数
+    pub hard_failures: usize,
+    /// 单封邮件遇到临时性失败后的重试次数（无论最终是否成功）
+    pub message_retries: usize,
+    /// 启用 `retry_round` 时，在最后的重试轮次中补发成功的邮件数（已计入 `email_count`）
+    pub retry_recovered: usize,
+    /// 未能保存到 `failed_emails_dir` 的失败邮件副本数
+    pub failed_saves: usize,
+    /// 启用 `dedup` 时因内容与已排队文件相同而跳过的文件数
+    pub duplicates_skipped: usize,
+    /// 被收件人域名过滤规则排除的收件人数
+    pub recipients_filtered: usize,
+    /// 因所有收件人都被过滤而跳过的邮件数
+    pub filtered_messages_skipped: usize,
+    /// EML批量发送中新建立的SMTP连接数
+    pub connections_opened: usize,
+    /// 批次沿用上一个批次留下的连接的次数
+    pub connections_reused: usize,
+    /// 因421等错误丢弃连接的次数
+    pub connections_reset: usize,
     pub error_details: HashMap<String, usize>,
     pub failed_files: HashMap<String, Vec<String>>,
+    /// 每个收件人的成功投递次数
+    pub recipient_delivered: HashMap<String, usize>,
+    /// 每个收件人的投递失败次数；部分收件人被拒绝的邮件只记在这里，不计入 `send_errors`
+    pub recipient_failed: HashMap<String, usize>,
+    /// 每封邮件收到的最后一条SMTP服务器应答 (文件路径, 应答行)，成功时通常包含远端队列ID
+    pub responses: Vec<(String, String)>,
+    /// 演练模式：只解析和构建邮件，未建立任何SMTP连接
+    pub dry_run: bool,
 }
 
 impl Stats {
@@ -24,11 +148,76 @@ impl Stats {
             total_duration: Duration::from_secs(0),
             parse_errors: 0,
             send_errors: 0,
+            soft_failures: 0,
+            hard_failures: 0,
+            message_retries: 0,
+            retry_recovered: 0,
+            failed_saves: 0,
+            duplicates_skipped: 0,
+            recipients_filtered: 0,
+            filtered_messages_skipped: 0,
+            connections_opened: 0,
+            connections_reused: 0,
+            connections_reset: 0,
             error_details: HashMap::new(),
             failed_files: HashMap::new(),
+            recipient_delivered: HashMap::new(),
+            recipient_failed: HashMap::new(),
+            responses: Vec::new(),
+            dry_run: false,
         }
     }
 
+    /// 把另一轮发送的统计累加到当前统计：计数相加，耗时、应答与失败文件追加，
+    /// 按错误类型和收件人的计数合并；`total_duration` 相加，演练标记任一为真即为真
+    pub fn merge(&mut self, other: &Stats) {
+        self.email_count += other.email_count;
+        self.parse_durations.extend_from_slice(&other.parse_durations);
+        self.send_durations.extend_from_slice(&other.send_durations);
+        self.total_duration += other.total_duration;
+        self.parse_errors += other.parse_errors;
+        self.send_errors += other.send_errors;
+        self.soft_failures += other.soft_failures;
+        self.hard_failures += other.hard_failures;
+        self.message_retries += other.message_retries;
+        self.retry_recovered += other.retry_recovered;
+        self.failed_saves += other.failed_saves;
+        self.duplicates_skipped += other.duplicates_skipped;
+        self.recipients_filtered += other.recipients_filtered;
+        self.filtered_messages_skipped += other.filtered_messages_skipped;
+        self.connections_opened += other.connections_opened;
+        self.connections_reused += other.connections_reused;
+        self.connections_reset += other.connections_reset;
+        for (error_type, count) in &other.error_details {
+            *self.error_details.entry(error_type.clone()).or_insert(0) += count;
+        }
+        for (error_type, files) in &other.failed_files {
+            self.failed_files
+                .entry(error_type.clone())
+                .or_default()
+                .extend(files.iter().cloned());
+        }
+        for (recipient, count) in &other.recipient_delivered {
+            *self.recipient_delivered.entry(recipient.clone()).or_insert(0) += count;
+        }
+        for (recipient, count) in &other.recipient_failed {
+            *self.recipient_failed.entry(recipient.clone()).or_insert(0) += count;
+        }
+        self.responses.extend(other.responses.iter().cloned());
+        self.dry_run |= other.dry_run;
+    }
+
+    /// 将失败文件与应答中的文件路径按 `labels` 替换（如JSONL记录的临时EML路径替换为 `来源:行号`）
+    pub(crate) fn relabel_files(&mut self, labels: &HashMap<String, String>) {
+        let relabel = |path: &mut String| {
+            if let Some(label) = labels.get(path.as_str()) {
+                *path = label.clone();
+            }
+        };
+        self.failed_files.values_mut().flatten().for_each(relabel);
+        self.responses.iter_mut().for_each(|(path, _)| relabel(path));
+    }
+
     pub fn increment_error(&mut self, error_type: &str, file_path: &str) {
         *self
             .error_details
@@ -39,6 +228,149 @@ impl Stats {
             .or_default()
             .push(file_path.to_string());
         self.send_errors += 1;
+        match FailureClass::classify(error_type) {
+            FailureClass::Soft => self.soft_failures += 1,
+            FailureClass::Hard => self.hard_failures += 1,
+            FailureClass::Unknown => {}
+        }
+    }
+
+    pub fn increment_parse_error(&mut self, error_type: &str, file_path: &str) {
+        *self
+            .error_details
+            .entry(error_type.to_string())
+            .or_insert(0) += 1;
+        self.failed_files
+            .entry(error_type.to_string())
+            .or_default()
+            .push(file_path.to_string());
+        self.parse_errors += 1;
+    }
+
+    pub fn record_recipient(&mut self, recipient: &str, delivered: bool) {
+        let counts = if delivered {
+            &mut self.recipient_delivered
+        } else {
+            &mut self.recipient_failed
+        };
+        *counts.entry(recipient.to_string()).or_insert(0) += 1;
+    }
+
+    pub fn record_response(&mut self, file_path: &str, reply: &str) {
+        self.responses.push((file_path.to_string(), reply.to_string()));
+    }
+
+    /// 成功发送的邮件数量
+    pub fn success_count(&self) -> usize {
+        self.email_count
+    }
+
+    /// 失败的邮件数量（发送错误与解析错误之和）
+    pub fn failed_count(&self) -> usize {
+        self.send_errors + self.parse_errors
+    }
+
+    /// 处理的邮件总数（成功与失败之和）
+    pub fn total_count(&self) -> usize {
+        self.success_count() + self.failed_count()
+    }
+
+    /// 生成机器可读的统计摘要，所有时间字段均为秒（浮点数）
+    pub fn to_json(&self) -> Value {
+        let total_parse_duration: Duration = self.parse_durations.iter().sum();
+        let total_send_duration: Duration = self.send_durations.iter().sum();
+
+        let mut recipients = serde_json::Map::new();
+        for recipient in self
+            .recipient_delivered
+            .keys()
+            .chain(self.recipient_failed.keys())
+        {
+            recipients.insert(
+                recipient.clone(),
+                json!({
+                    "delivered": self.recipient_delivered.get(recipient).copied().unwrap_or(0),
+                    "failed": self.recipient_failed.get(recipient).copied().unwrap_or(0),
+                }),
+            );
+        }
+
+        json!({
+            "dry_run": self.dry_run,
+            "email_count": self.email_count,
+            "total": self.total_count(),
+            "success": self.success_count(),
+            "failed": self.failed_count(),
+            "parse_errors": self.parse_errors,
+            "send_errors": self.send_errors,
+            "soft_failures": self.soft_failures,
+            "hard_failures": self.hard_failures,
+            "message_retries": self.message_retries,
+            "retry_recovered": self.retry_recovered,
+            "failed_saves": self.failed_saves,
+            "duplicates_skipped": self.duplicates_skipped,
+            "recipients_filtered": self.recipients_filtered,
+            "filtered_messages_skipped": self.filtered_messages_skipped,
+            "connections": {
+                "opened": self.connections_opened,
+                "reused": self.connections_reused,
+                "reset": self.connections_reset,
+            },
+            "error_details": self.error_details,
+            "error_classes": self
+                .error_details
+                .keys()
+                .map(|error_type| (error_type.clone(), FailureClass::classify(error_type).as_str()))
+                .collect::<HashMap<_, _>>(),
+            "failed_files": self.failed_files,
+            "recipients": recipients,
+            "responses": self
+                .responses
+                .iter()
+                .map(|(file, reply)| json!({ "file": file, "reply": reply }))
+                .collect::<Vec<_>>(),
+            "parse_duration_secs": total_parse_duration.as_secs_f64(),
+            "send_duration_secs": total_send_duration.as_secs_f64(),
+            "total_duration_secs": self.total_duration.as_secs_f64(),
+            "parse_qps": self.calculate_qps(self.email_count, total_parse_duration),
+            "send_qps": self.calculate_qps(self.email_count, total_send_duration),
+            "actual_qps": self.calculate_qps(self.email_count, self.total_duration),
+            "parse_latency_ms": Percentiles::of(&self.parse_durations).map(Percentiles::to_json),
+            "send_latency_ms": Percentiles::of(&self.send_durations).map(Percentiles::to_json),
+        })
+    }
+
+    /// 将 JSON 统计摘要写入文件，路径为 "-" 时输出到标准输出
+    pub fn write_json(&self, path: &str) -> std::io::Result<()> {
+        let content = serde_json::to_string_pretty(&self.to_json())?;
+        if path == "-" {
+            let mut stdout = std::io::stdout().lock();
+            writeln!(stdout, "{}", content)
+        } else {
+            std::fs::write(path, content + "\n")
+        }
+    }
+
+    /// 将失败文件导出为CSV（列：file_path,error_type,timestamp）
+    ///
+    /// 目前不记录单个文件的失败时间，timestamp 统一为导出时刻（即运行结束时间）。
+    pub fn write_failed_csv(&self, path: &str) -> std::io::Result<()> {
+        let timestamp = chrono::Local::now().to_rfc3339();
+        let mut error_types: Vec<&String> = self.failed_files.keys().collect();
+        error_types.sort();
+
+        let mut content = String::from("file_path,error_type,timestamp\n");
+        for error_type in error_types {
+            for file_path in &self.failed_files[error_type] {
+                content.push_str(&format!(
+                    "{},{},{}\n",
+                    csv_field(file_path),
+                    csv_field(error_type),
+                    timestamp
+                ));
+            }
+        }
+        std::fs::write(path, content)
     }
 
     fn calculate_qps(&self, count: usize, duration: Duration) -> f64 {
@@ -50,32 +382,100 @@ impl Stats {
     }
 }
 
+impl Serialize for Stats {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_json().serialize(serializer)
+    }
+}
+
 impl fmt::Display for Stats {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "{}", tr("core.stats.report_title"))?;
         writeln!(f, "{}", tr("core.stats.separator"))?;
+        if self.dry_run {
+            writeln!(f, "{}", tr("core.stats.dry_run_notice"))?;
+        }
         writeln!(f, "{}", tr("core.stats.basic_stats"))?;
-        writeln!(
-            f,
-            "{}",
-            tr_with_args("core.stats.total_processed", &[("count", &self.email_count.to_string())])
-        )?;
-        writeln!(
-            f,
-            "{}",
-            tr_with_args(
-                "core.stats.success_sent",
-                &[("count", &(self.email_count - self.send_errors - self.parse_errors).to_string())]
-            )
-        )?;
-        writeln!(
-            f,
-            "{}",
-            tr_with_args(
-                "core.stats.total_failed",
-                &[("count", &(self.send_errors + self.parse_errors).to_string())]
-            )
-        )?;
+        writeln!(f, "{}", tr_plural("core.stats.total_processed", self.total_count() as u64, &[]))?;
+        writeln!(f, "{}", tr_plural("core.stats.success_sent", self.success_count() as u64, &[]))?;
+        writeln!(f, "{}", tr_plural("core.stats.total_failed", self.failed_count() as u64, &[]))?;
+        if self.soft_failures + self.hard_failures > 0 {
+            writeln!(
+                f,
+                "{}",
+                tr_with_args(
+                    "core.stats.failure_classes",
+                    &[
+                        ("soft", &self.soft_failures.to_string()),
+                        ("hard", &self.hard_failures.to_string())
+                    ]
+                )
+            )?;
+        }
+        if self.message_retries > 0 {
+            writeln!(
+                f,
+                "{}",
+                tr_with_args(
+                    "core.stats.message_retries",
+                    &[("count", &self.message_retries.to_string())]
+                )
+            )?;
+        }
+        if self.retry_recovered > 0 {
+            writeln!(
+                f,
+                "{}",
+                tr_with_args(
+                    "core.stats.retry_recovered",
+                    &[("count", &self.retry_recovered.to_string())]
+                )
+            )?;
+        }
+        if self.failed_saves > 0 {
+            writeln!(
+                f,
+                "{}",
+                tr_with_args("core.stats.failed_saves", &[("count", &self.failed_saves.to_string())])
+            )?;
+        }
+        if self.duplicates_skipped > 0 {
+            writeln!(
+                f,
+                "{}",
+                tr_with_args(
+                    "core.stats.duplicates_skipped",
+                    &[("count", &self.duplicates_skipped.to_string())]
+                )
+            )?;
+        }
+        if self.recipients_filtered > 0 {
+            writeln!(
+                f,
+                "{}",
+                tr_with_args(
+                    "core.stats.recipients_filtered",
+                    &[
+                        ("count", &self.recipients_filtered.to_string()),
+                        ("skipped", &self.filtered_messages_skipped.to_string())
+                    ]
+                )
+            )?;
+        }
+        if self.connections_opened > 0 {
+            writeln!(
+                f,
+                "{}",
+                tr_with_args(
+                    "core.stats.connections",
+                    &[
+                        ("opened", &self.connections_opened.to_string()),
+                        ("reused", &self.connections_reused.to_string()),
+                        ("reset", &self.connections_reset.to_string())
+                    ]
+                )
+            )?;
+        }
 
         if !self.error_details.is_empty() {
             writeln!(f, "\n{}", tr("core.stats.error_classification"))?;
@@ -83,18 +483,23 @@ impl fmt::Display for Stats {
             sorted_errors.sort_by(|a, b| b.1.cmp(a.1));
 
             for (error_type, count) in sorted_errors {
-                let percent = if self.email_count > 0 {
-                    (*count as f64 / self.email_count as f64) * 100.0
+                let percent = if self.total_count() > 0 {
+                    (*count as f64 / self.total_count() as f64) * 100.0
                 } else {
                     0.0
                 };
+                let labeled_type = match FailureClass::classify(error_type) {
+                    FailureClass::Soft => format!("[{}] {}", tr("core.stats.class_soft"), error_type),
+                    FailureClass::Hard => format!("[{}] {}", tr("core.stats.class_hard"), error_type),
+                    FailureClass::Unknown => error_type.clone(),
+                };
                 writeln!(
                     f,
                     "{}",
                     tr_with_args(
                         "core.stats.error_type_count",
                         &[
-                            ("type", error_type),
+                            ("type", &labeled_type),
                             ("count", &count.to_string()),
                             ("percent", &format!("{:.1}", percent))
                         ]
@@ -113,6 +518,34 @@ impl fmt::Display for Stats {
             }
         }
 
+        if !self.recipient_delivered.is_empty() || !self.recipient_failed.is_empty() {
+            writeln!(f, "\n{}", tr("core.stats.recipient_results"))?;
+            let mut recipients: Vec<&String> = self
+                .recipient_delivered
+                .keys()
+                .chain(self.recipient_failed.keys())
+                .collect();
+            recipients.sort();
+            recipients.dedup();
+
+            for recipient in recipients {
+                let delivered = self.recipient_delivered.get(recipient).copied().unwrap_or(0);
+                let failed = self.recipient_failed.get(recipient).copied().unwrap_or(0);
+                writeln!(
+                    f,
+                    "{}",
+                    tr_with_args(
+                        "core.stats.recipient_result_item",
+                        &[
+                            ("recipient", recipient.as_str()),
+                            ("delivered", &delivered.to_string()),
+                            ("failed", &failed.to_string())
+                        ]
+                    )
+                )?;
+            }
+        }
+
         // Calculate total parse and send duration
         let total_parse_duration: Duration = self.parse_durations.iter().sum();
         let total_send_duration: Duration = self.send_durations.iter().sum();
@@ -130,6 +563,9 @@ impl fmt::Display for Stats {
                 ]
             )
         )?;
+        if let Some(percentiles) = Percentiles::of(&self.parse_durations) {
+            percentiles.write_line(f, "core.stats.parse_percentiles")?;
+        }
 
         // Calculate send QPS
         let send_qps = self.calculate_qps(self.email_count, total_send_duration);
@@ -144,6 +580,9 @@ impl fmt::Display for Stats {
                 ]
             )
         )?;
+        if let Some(percentiles) = Percentiles::of(&self.send_durations) {
+            percentiles.write_line(f, "core.stats.send_percentiles")?;
+        }
 
         // Calculate actual total time
         let total_secs = self.total_duration.as_secs_f64();
@@ -163,3 +602,210 @@ impl fmt::Display for Stats {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_percentiles() {
+        let mut stats = Stats::new();
+        stats.send_durations = (1..=100).rev().map(Duration::from_millis).collect();
+        stats.parse_durations = vec![Duration::from_millis(4), Duration::from_millis(2)];
+
+        let send = Percentiles::of(&stats.send_durations).unwrap();
+        assert_eq!(send.p50, Duration::from_millis(50));
+        assert_eq!(send.p90, Duration::from_millis(90));
+        assert_eq!(send.p99, Duration::from_millis(99));
+        let parse = Percentiles::of(&stats.parse_durations).unwrap();
+        assert_eq!((parse.p50, parse.p99), (Duration::from_millis(2), Duration::from_millis(4)));
+        assert_eq!(Percentiles::of(&[]), None);
+
+        let json = stats.to_json();
+        assert_eq!(json["send_latency_ms"]["p90"], 90.0);
+        assert_eq!(json["parse_latency_ms"]["p50"], 2.0);
+        assert!(stats.to_string().contains(&tr_with_args(
+            "core.stats.send_percentiles",
+            &[("p50", "50.0"), ("p90", "90.0"), ("p99", "99.0")]
+        )));
+        assert!(Stats::new().to_json()["send_latency_ms"].is_null());
+    }
+
+    #[test]
+    fn test_merge_aggregates_every_field() {
+        let mut total = Stats::new();
+        total.email_count = 2;
+        total.parse_durations = vec![Duration::from_millis(10)];
+        total.send_durations = vec![Duration::from_millis(100)];
+        total.total_duration = Duration::from_secs(1);
+        total.message_retries = 1;
+        total.increment_error("421 4.7.0 Try again later", "a.eml");
+        total.record_recipient("a@example.com", true);
+        total.record_response("b.eml", "250 Ok: queued as 1");
+
+        let mut round = Stats::new();
+        round.email_count = 3;
+        round.parse_durations = vec![Duration::from_millis(20), Duration::from_millis(30)];
+        round.send_durations = vec![Duration::from_millis(200)];
+        round.total_duration = Duration::from_secs(2);
+        round.message_retries = 2;
+        round.retry_recovered = 1;
+        round.failed_saves = 1;
+        round.duplicates_skipped = 2;
+        round.recipients_filtered = 3;
+        round.connections_opened = 2;
+        round.connections_reused = 4;
+        round.connections_reset = 1;
+        round.dry_run = true;
+        round.increment_error("421 4.7.0 Try again later", "c.eml");
+        round.increment_error("550 5.1.1 User unknown", "d.eml");
+        round.increment_error("邮件发送超时", "f.eml");
+        round.increment_parse_error("解析失败", "e.eml");
+        round.record_recipient("a@example.com", true);
+        round.record_recipient("b@example.com", false);
+        round.record_response("c.eml", "250 Ok: queued as 2");
+
+        total.merge(&round);
+
+        assert_eq!(total.email_count, 5);
+        assert_eq!(total.parse_durations.len(), 3);
+        assert_eq!(
+            total.send_durations,
+            [Duration::from_millis(100), Duration::from_millis(200)]
+        );
+        assert_eq!(total.total_duration, Duration::from_secs(3));
+        assert_eq!(total.parse_errors, 1);
+        assert_eq!(total.send_errors, 4);
+        assert_eq!(total.soft_failures, 2);
+        assert_eq!(total.hard_failures, 1);
+        assert_eq!(total.message_retries, 3);
+        assert_eq!(total.retry_recovered, 1);
+        assert_eq!(total.failed_saves, 1);
+        assert_eq!(total.duplicates_skipped, 2);
+        assert_eq!(total.recipients_filtered, 3);
+        assert_eq!(
+            (total.connections_opened, total.connections_reused, total.connections_reset),
+            (2, 4, 1)
+        );
+        assert_eq!(total.error_details["421 4.7.0 Try again later"], 2);
+        assert_eq!(total.error_details["550 5.1.1 User unknown"], 1);
+        assert_eq!(total.error_details["邮件发送超时"], 1);
+        assert_eq!(total.failed_files["421 4.7.0 Try again later"], ["a.eml", "c.eml"]);
+        assert_eq!(total.failed_files["解析失败"], ["e.eml"]);
+        assert_eq!(total.recipient_delivered["a@example.com"], 2);
+        assert_eq!(total.recipient_failed["b@example.com"], 1);
+        assert_eq!(total.responses.len(), 2);
+        assert_eq!(total.responses[1].1, "250 Ok: queued as 2");
+        assert!(total.dry_run);
+    }
+
+    #[test]
+    fn test_to_json_uses_seconds_and_counts() {
+        let mut stats = Stats::new();
+        stats.email_count = 4;
+        stats.send_durations = vec![Duration::from_millis(500), Duration::from_millis(1500)];
+        stats.total_duration = Duration::from_secs(2);
+        stats.increment_error("邮件发送超时", "a.eml");
+        stats.record_recipient("a@example.com", true);
+        stats.record_response("b.eml", "250 2.0.0 Ok: queued as 4F2A1");
+
+        let value = stats.to_json();
+        assert_eq!(value["email_count"], 4);
+        assert_eq!(value["total"], 5);
+        assert_eq!(value["success"], 4);
+        assert_eq!(value["failed"], 1);
+        assert_eq!(value["error_details"]["邮件发送超时"], 1);
+        assert_eq!(value["failed_files"]["邮件发送超时"][0], "a.eml");
+        assert_eq!(value["recipients"]["a@example.com"]["delivered"], 1);
+        assert_eq!(value["responses"][0]["file"], "b.eml");
+        assert_eq!(value["responses"][0]["reply"], "250 2.0.0 Ok: queued as 4F2A1");
+        assert_eq!(value["send_duration_secs"].as_f64(), Some(2.0));
+        assert_eq!(value["send_qps"].as_f64(), Some(2.0));
+        assert_eq!(serde_json::to_value(&stats).unwrap(), value);
+    }
+
+    #[test]
+    fn test_classifies_soft_and_hard_failures() {
+        assert_eq!(
+            FailureClass::classify("邮件发送失败: 450 4.2.0 Mailbox busy"),
+            FailureClass::Soft
+        );
+        assert_eq!(
+            FailureClass::classify(
+                "Unexpected reply: Code: 550, Enhanced code: 5.1.1, Message: no such user"
+            ),
+            FailureClass::Hard
+        );
+        assert_eq!(
+            FailureClass::classify("421 Service not available"),
+            FailureClass::Soft
+        );
+        assert_eq!(FailureClass::classify("邮件发送超时"), FailureClass::Unknown);
+        assert_eq!(
+            FailureClass::classify("读取文件失败: /mail/550.eml"),
+            FailureClass::Unknown
+        );
+
+        let mut stats = Stats::new();
+        stats.increment_error("邮件发送失败: 450 4.2.0 Mailbox busy", "a.eml");
+        stats.increment_error("邮件发送失败: 450 4.2.0 Mailbox busy", "b.eml");
+        stats.increment_error("设置收件人 x@example.com 失败: 550 No such user", "c.eml");
+        stats.increment_error("SMTP连接失败Plain", "d.eml");
+        stats.increment_parse_error("无法解析邮件文件", "e.eml");
+
+        assert_eq!(stats.soft_failures, 2);
+        assert_eq!(stats.hard_failures, 1);
+        assert_eq!(stats.send_errors, 4);
+        let value = stats.to_json();
+        assert_eq!(value["soft_failures"], 2);
+        assert_eq!(value["hard_failures"], 1);
+        assert_eq!(
+            value["error_classes"]["邮件发送失败: 450 4.2.0 Mailbox busy"],
+            "soft"
+        );
+        assert_eq!(value["error_classes"]["SMTP连接失败Plain"], "unknown");
+    }
+
+    /// 解析RFC 4180格式的CSV（字段内可包含引号转义的逗号和引号）
+    fn parse_csv(content: &str) -> Vec<Vec<String>> {
+        let mut rows = Vec::new();
+        for line in content.lines() {
+            let mut fields = vec![String::new()];
+            let mut chars = line.chars().peekable();
+            let mut quoted = false;
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' if quoted && chars.peek() == Some(&'"') => {
+                        chars.next();
+                        fields.last_mut().unwrap().push('"');
+                    }
+                    '"' => quoted = !quoted,
+                    ',' if !quoted => fields.push(String::new()),
+                    _ => fields.last_mut().unwrap().push(c),
+                }
+            }
+            rows.push(fields);
+        }
+        rows
+    }
+
+    #[test]
+    fn test_write_failed_csv_quotes_fields() {
+        let mut stats = Stats::new();
+        stats.increment_error("邮件发送超时", "/mail/a.eml");
+        stats.increment_error("邮件发送超时", "/mail/b, \"draft\".eml");
+        stats.increment_parse_error("解析邮件失败", "/mail/c.eml");
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("failed.csv");
+
+        stats.write_failed_csv(path.to_str().unwrap()).unwrap();
+
+        let rows = parse_csv(&std::fs::read_to_string(&path).unwrap());
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0], vec!["file_path", "error_type", "timestamp"]);
+        assert!(rows.iter().all(|row| row.len() == 3));
+        assert_eq!(rows[1][..2], ["/mail/c.eml", "解析邮件失败"]);
+        assert_eq!(rows[3][..2], ["/mail/b, \"draft\".eml", "邮件发送超时"]);
+        assert!(chrono::DateTime::parse_from_rfc3339(&rows[3][2]).is_ok());
+    }
+}
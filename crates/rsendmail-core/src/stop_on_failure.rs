@@ -0,0 +1,108 @@
+//! 遇到第一封指定种类的失败邮件即终止发送
+//!
+//! 默认情况下失败的邮件只计入统计，其余邮件照常发送。以下两种模式共用 [`StopOnFailure`]：
+//!
+//! - `strict_parse`：第一封无法解析的邮件（[`FailureKind::Parse`]），便于先修正邮件文件再重新发送；
+//! - `fail_fast`：第一封发送失败的邮件（[`FailureKind::Send`]），用于CI冒烟测试中尽早暴露错误的配置。
+//!
+//! 策略经进度回调的 [`Delivery::failure`] 发现匹配的失败，记录其路径与错误，并将共享的运行状态
+//! 切换为排空：正在发送的邮件照常完成，已建立的连接照常发送QUIT后关闭，但不再开始新的邮件，
+//! 本轮发送以错误结束。
+
+use log::error;
+use std::sync::{Arc, Mutex};
+
+use crate::config::Config;
+use crate::progress::{Delivery, FailureKind, ProgressObserver};
+use crate::shutdown::RunControl;
+
+/// 所有进程组共享，记录第一封种类为 `kind` 的失败邮件
+pub struct StopOnFailure {
+    kind: FailureKind,
+    first_failure: Mutex<Option<(String, String)>>,
+    running: RunControl,
+}
+
+impl StopOnFailure {
+    pub fn new(kind: FailureKind, running: RunControl) -> Self {
+        Self {
+            kind,
+            first_failure: Mutex::new(None),
+            running,
+        }
+    }
+
+    /// 按 `strict_parse` 与 `fail_fast` 创建启用的策略，严格解析在前
+    pub fn from_config(config: &Config, running: &RunControl) -> Vec<Arc<Self>> {
+        [
+            (config.strict_parse, FailureKind::Parse),
+            (config.fail_fast, FailureKind::Send),
+        ]
+        .into_iter()
+        .filter(|(enabled, _)| *enabled)
+        .map(|(_, kind)| Arc::new(Self::new(kind, running.clone())))
+        .collect()
+    }
+
+    /// 策略匹配的失败种类
+    pub fn kind(&self) -> FailureKind {
+        self.kind
+    }
+
+    /// 记录一封邮件的失败，种类匹配且是第一封时停止开始新的邮件
+    pub fn record(&self, path: &str, kind: FailureKind, error: &str) {
+        if kind != self.kind {
+            return;
+        }
+        let mut first_failure = self.first_failure.lock().unwrap();
+        if first_failure.is_none() {
+            match kind {
+                FailureKind::Parse => error!("严格解析模式：邮件 {} 无法解析，停止发送", path),
+                FailureKind::Send => {
+                    error!("快速失败模式：邮件 {} 发送失败（{}），停止发送", path, error)
+                }
+            }
+            *first_failure = Some((path.to_string(), error.to_string()));
+            self.running.drain();
+        }
+    }
+
+    /// 第一封匹配的失败邮件 (路径, 错误)
+    pub fn first_failure(&self) -> Option<(String, String)> {
+        self.first_failure.lock().unwrap().clone()
+    }
+}
+
+/// 将每封失败邮件交给所有启用的 [`StopOnFailure`]
+impl ProgressObserver for Vec<Arc<StopOnFailure>> {
+    fn delivery(&self, delivery: &Delivery<'_>) {
+        if let Some(kind) = delivery.failure {
+            for policy in self {
+                policy.record(delivery.path, kind, delivery.response);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_only_first_failure_of_matching_kind() {
+        let running = RunControl::new();
+        let fail_fast = StopOnFailure::new(FailureKind::Send, running.clone());
+
+        fail_fast.record("broken.eml", FailureKind::Parse, "无法解析邮件文件");
+        assert!(running.is_running());
+        assert_eq!(fail_fast.first_failure(), None);
+
+        fail_fast.record("a.eml", FailureKind::Send, "550 rejected");
+        fail_fast.record("b.eml", FailureKind::Send, "421 closing");
+        assert!(!running.is_running());
+        assert_eq!(
+            fail_fast.first_failure(),
+            Some(("a.eml".to_string(), "550 rejected".to_string()))
+        );
+    }
+}
@@ -0,0 +1,142 @@
+//! 长时间运行期间的实时吞吐量与预计剩余时间
+//!
+//! EML 批量发送时，各进程组经进度回调累加已处理（成功或失败）的邮件数；后台任务每隔
+//! [`REPORT_INTERVAL`] 根据该间隔内处理的数量计算瞬时 QPS，按开始以来的平均速率和剩余
+//! 文件数估算剩余时间，写入日志并通过 [`SendProgress::on_throughput`] 通知调用方。
+
+use log::info;
+use rsendmail_i18n::tr_with_args;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use crate::progress::{Delivery, SendProgress};
+
+/// 两次吞吐量报告之间的间隔
+pub const REPORT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 根据已处理数量、总数和已用时间估算剩余时间，尚未处理任何邮件时无法估算
+pub fn estimate_eta(done: usize, total: usize, elapsed: Duration) -> Option<Duration> {
+    if done == 0 {
+        return None;
+    }
+    let remaining = total.saturating_sub(done);
+    Some(elapsed.mul_f64(remaining as f64 / done as f64))
+}
+
+/// 格式化为 `HH:MM:SS`
+pub fn format_hms(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{:02}:{:02}:{:02}", secs / 3600, (secs % 3600) / 60, secs % 60)
+}
+
+/// 统计已处理的邮件数，并把事件转发给调用方的进度回调
+struct CountingProgress {
+    done: Arc<AtomicUsize>,
+    inner: Option<Arc<dyn SendProgress>>,
+}
+
+impl SendProgress for CountingProgress {
+    fn on_email_sent(&self, path: &str, duration: Duration) {
+        self.done.fetch_add(1, Ordering::Relaxed);
+        if let Some(inner) = &self.inner {
+            inner.on_email_sent(path, duration);
+        }
+    }
+
+    fn on_email_failed(&self, path: &str, error: &str) {
+        self.done.fetch_add(1, Ordering::Relaxed);
+        if let Some(inner) = &self.inner {
+            inner.on_email_failed(path, error);
+        }
+    }
+
+    fn on_batch_start(&self, group: usize, size: usize) {
+        if let Some(inner) = &self.inner {
+            inner.on_batch_start(group, size);
+        }
+    }
+
+    fn on_delivery(&self, delivery: &Delivery<'_>) {
+        if let Some(inner) = &self.inner {
+            inner.on_delivery(delivery);
+        }
+    }
+
+    fn on_throughput(&self, qps: f64, eta: Option<Duration>) {
+        if let Some(inner) = &self.inner {
+            inner.on_throughput(qps, eta);
+        }
+    }
+}
+
+/// 定期报告吞吐量的后台任务，drop 时停止
+pub(crate) struct ThroughputMonitor {
+    task: JoinHandle<()>,
+}
+
+impl ThroughputMonitor {
+    /// 启动后台报告任务，返回监视器以及发送任务应使用的进度回调
+    pub fn start(
+        total: usize,
+        inner: Option<Arc<dyn SendProgress>>,
+    ) -> (Self, Arc<dyn SendProgress>) {
+        let done = Arc::new(AtomicUsize::new(0));
+        let progress = Arc::new(CountingProgress {
+            done: done.clone(),
+            inner: inner.clone(),
+        });
+        let task = tokio::spawn(async move {
+            let start = Instant::now();
+            let mut ticker = tokio::time::interval_at(start + REPORT_INTERVAL, REPORT_INTERVAL);
+            let mut last_done = 0;
+            loop {
+                ticker.tick().await;
+                let done = done.load(Ordering::Relaxed);
+                let qps = done.saturating_sub(last_done) as f64 / REPORT_INTERVAL.as_secs_f64();
+                last_done = done;
+                let eta = estimate_eta(done, total, start.elapsed());
+                info!(
+                    "{}",
+                    tr_with_args(
+                        "core.mailer.live_throughput",
+                        &[
+                            ("done", &done.to_string()),
+                            ("total", &total.to_string()),
+                            ("qps", &format!("{:.2}", qps)),
+                            ("eta", &eta.map_or_else(|| "--:--:--".to_string(), format_hms)),
+                        ]
+                    )
+                );
+                if let Some(inner) = &inner {
+                    inner.on_throughput(qps, eta);
+                }
+            }
+        });
+        (Self { task }, progress)
+    }
+}
+
+impl Drop for ThroughputMonitor {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eta_from_average_rate() {
+        assert_eq!(
+            estimate_eta(25, 100, Duration::from_secs(10)),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(estimate_eta(100, 100, Duration::from_secs(10)), Some(Duration::ZERO));
+        assert_eq!(estimate_eta(0, 100, Duration::from_secs(10)), None);
+        assert_eq!(format_hms(Duration::from_secs(3 * 3600 + 62)), "03:01:02");
+    }
+}
@@ -0,0 +1,132 @@
+//! TLS连接的证书校验
+//!
+//! 默认按系统内置的根证书校验服务器证书，`accept_invalid_certs` 跳过所有校验。
+//! 设置 `pinned_cert_sha256` 后改为证书固定：只有服务器证书（叶子证书）DER编码的
+//! SHA-256 指纹与配置一致时握手才成功，不再检查签发者、有效期和主机名，
+//! 适合使用自签名证书的内部中继；指纹不一致时无论 `accept_invalid_certs` 如何都拒绝连接。
+
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tokio_rustls::rustls::client::danger::{
+    HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+};
+use tokio_rustls::rustls::crypto::{ring, verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use tokio_rustls::rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use tokio_rustls::rustls::{ClientConfig, DigitallySignedStruct, Error, SignatureScheme};
+use tokio_rustls::TlsConnector;
+
+use mail_send::smtp::tls::build_tls_connector;
+use rsendmail_i18n::tr_with_args;
+
+use crate::config::Config;
+
+/// 解析十六进制的SHA-256指纹，忽略大小写以及字节之间的 `:` 与空白
+pub fn parse_fingerprint(fingerprint: &str) -> Option<[u8; 32]> {
+    let hex: Vec<u8> = fingerprint
+        .bytes()
+        .filter(|byte| *byte != b':' && !byte.is_ascii_whitespace())
+        .collect();
+    if hex.len() != 64 {
+        return None;
+    }
+    let mut digest = [0u8; 32];
+    for (byte, pair) in digest.iter_mut().zip(hex.chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(digest)
+}
+
+/// 以 `AA:BB:...` 形式显示指纹
+fn format_fingerprint(digest: &[u8]) -> String {
+    digest
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// 按配置创建TLS连接器：设置了有效的 `pinned_cert_sha256` 时使用证书固定，
+/// 否则按 `accept_invalid_certs` 使用 mail-send 的默认校验
+pub fn tls_connector(config: &Config) -> TlsConnector {
+    let Some(fingerprint) = config.pinned_cert_sha256.as_deref().and_then(parse_fingerprint) else {
+        return build_tls_connector(config.accept_invalid_certs);
+    };
+    let verifier = PinnedCertVerifier {
+        fingerprint,
+        provider: Arc::new(ring::default_provider()),
+    };
+    let client_config = ClientConfig::builder()
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth();
+    TlsConnector::from(Arc::new(client_config))
+}
+
+/// 只接受指纹与配置一致的服务器证书；握手签名仍按证书公钥正常校验
+#[derive(Debug)]
+struct PinnedCertVerifier {
+    fingerprint: [u8; 32],
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for PinnedCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, Error> {
+        let actual = Sha256::digest(end_entity.as_ref());
+        if actual.as_slice() == self.fingerprint {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(Error::General(tr_with_args(
+                "core.mailer.pinned_cert_mismatch",
+                &[
+                    ("expected", &format_fingerprint(&self.fingerprint)),
+                    ("actual", &format_fingerprint(&actual)),
+                ],
+            )))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, Error> {
+        verify_tls12_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, Error> {
+        verify_tls13_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fingerprint_accepts_colons_and_case() {
+        let plain = "00112233445566778899aabbccddeeff00112233445566778899AABBCCDDEEFF";
+        let digest = parse_fingerprint(plain).unwrap();
+        assert_eq!(digest[1], 0x11);
+        assert_eq!(digest[31], 0xff);
+        assert_eq!(parse_fingerprint(&format_fingerprint(&digest)), Some(digest));
+        assert_eq!(parse_fingerprint("00:11"), None);
+        assert_eq!(parse_fingerprint(&plain.replace('0', "g")), None);
+    }
+}
@@ -0,0 +1,260 @@
+//! 发送前的EML结构检查
+//!
+//! `Mailer::validate` 按与发送相同的规则收集EML文件，逐个读取并检查头部结构、编码、
+//! 换行与地址，生成 [`ValidationReport`]，不建立任何SMTP连接。与演练模式不同，这里
+//! 不构建待发送的邮件，而是报告 `MessageParser` 会静默容忍、但可能导致服务器拒收或
+//! 投递异常的头部问题。发件人与收件人由配置（`from`、`to`、收件人文件）提供时，
+//! 不要求邮件头中包含对应地址。
+
+use mail_parser::MessageParser;
+use rsendmail_i18n::{tr, tr_with_args};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::compression;
+use crate::config::Config;
+use crate::mailer::{extract_all_emails, extract_all_recipients, is_valid_address, parse_global_recipients};
+
+/// 在一封邮件中只允许出现一次的邮件头（RFC 5322 3.6）
+const SINGLETON_HEADERS: [&str; 11] = [
+    "from",
+    "sender",
+    "reply-to",
+    "to",
+    "cc",
+    "bcc",
+    "subject",
+    "date",
+    "message-id",
+    "in-reply-to",
+    "references",
+];
+
+/// 一个EML文件中发现的问题
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// 读取或解压文件失败
+    Unreadable(String),
+    /// `MessageParser` 无法解析
+    Unparseable,
+    /// 头部中既不是 `名称: 值` 也不是折叠续行的行（行号从1开始）
+    MalformedHeaderLine(usize),
+    /// 头部包含无效的 UTF-8 字节（未按 RFC 2047 编码的8位字符）
+    InvalidHeaderEncoding,
+    /// 存在不与CR配对的LF，原样写入DATA时可能被服务器拒收
+    BareLineFeed,
+    /// 应只出现一次的邮件头出现多次
+    DuplicateHeader(String),
+    /// 未配置 `from` 且邮件头中没有可用的发件人地址
+    MissingFrom,
+    /// 未配置收件人且邮件头中没有可用的收件人地址
+    MissingRecipients,
+    /// 邮件头中语法无效的地址
+    InvalidAddress(String),
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Self::Unreadable(error) => tr_with_args("core.validate.unreadable", &[("error", error)]),
+            Self::Unparseable => tr("core.validate.unparseable"),
+            Self::MalformedHeaderLine(line) => {
+                tr_with_args("core.validate.malformed_header", &[("line", &line.to_string())])
+            }
+            Self::InvalidHeaderEncoding => tr("core.validate.header_encoding"),
+            Self::BareLineFeed => tr("core.validate.bare_lf"),
+            Self::DuplicateHeader(name) => {
+                tr_with_args("core.validate.duplicate_header", &[("header", name)])
+            }
+            Self::MissingFrom => tr("core.validate.missing_from"),
+            Self::MissingRecipients => tr("core.validate.missing_recipients"),
+            Self::InvalidAddress(address) => {
+                tr_with_args("core.validate.invalid_address", &[("address", address)])
+            }
+        };
+        write!(f, "{}", text)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileReport {
+    pub path: String,
+    pub problems: Vec<Problem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// 检查的文件数量
+    pub files: usize,
+    /// 存在问题的文件，按收集顺序排列
+    pub reports: Vec<FileReport>,
+}
+
+impl ValidationReport {
+    /// 所有文件都没有问题
+    pub fn is_clean(&self) -> bool {
+        self.reports.is_empty()
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", tr("core.validate.title"))?;
+        for report in &self.reports {
+            writeln!(f, "    {}", report.path)?;
+            for problem in &report.problems {
+                writeln!(f, "        - {}", problem)?;
+            }
+        }
+        write!(
+            f,
+            "{}",
+            tr_with_args(
+                "core.validate.summary",
+                &[
+                    ("files", &self.files.to_string()),
+                    ("problems", &self.reports.len().to_string()),
+                ]
+            )
+        )
+    }
+}
+
+/// 检查一个EML文件，返回发现的问题（没有问题时为空）
+pub(crate) fn check_file(config: &Config, path: &str) -> Vec<Problem> {
+    let content = fs::read(path).and_then(|raw| {
+        if config.decompress {
+            compression::decompress_if_gzip(raw)
+        } else {
+            Ok(raw)
+        }
+    });
+    match content {
+        Ok(content) => check_content(config, path, &content),
+        Err(e) => vec![Problem::Unreadable(e.to_string())],
+    }
+}
+
+fn check_content(config: &Config, path: &str, content: &[u8]) -> Vec<Problem> {
+    let mut problems = check_header_section(content);
+    if has_bare_line_feed(content) {
+        problems.push(Problem::BareLineFeed);
+    }
+
+    let Some(message) = MessageParser::default().parse(content) else {
+        problems.push(Problem::Unparseable);
+        return problems;
+    };
+
+    let header_addresses = [message.from(), message.to(), message.cc(), message.bcc()]
+        .into_iter()
+        .flat_map(extract_all_emails);
+    for address in header_addresses {
+        if !is_valid_address(&address) {
+            problems.push(Problem::InvalidAddress(address));
+        }
+    }
+
+    let configured_from = config.from.as_deref().is_some_and(|from| !from.is_empty());
+    if !configured_from && !extract_all_emails(message.from()).iter().any(|a| is_valid_address(a)) {
+        problems.push(Problem::MissingFrom);
+    }
+
+    let sidecar = config.recipient_from_sidecar && Path::new(&format!("{}.to", path)).is_file();
+    let configured_recipients = sidecar || parse_global_recipients(config).is_some();
+    if !configured_recipients
+        && !extract_all_recipients(&message, config.envelope_cc_bcc)
+            .iter()
+            .any(|a| is_valid_address(a))
+    {
+        problems.push(Problem::MissingRecipients);
+    }
+    problems
+}
+
+/// 检查头部（第一个空行之前）的每一行：格式、编码与重复的邮件头
+fn check_header_section(content: &[u8]) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let mut seen: Vec<String> = Vec::new();
+    let mut header_end = content.len();
+    let mut offset = 0;
+
+    for (index, line) in content.split(|&byte| byte == b'\n').enumerate() {
+        let line_start = offset;
+        offset += line.len() + 1;
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.is_empty() {
+            header_end = line_start;
+            break;
+        }
+        // 折叠续行属于上一个邮件头，第一行不能是续行
+        if index > 0 && matches!(line[0], b' ' | b'\t') {
+            continue;
+        }
+        let name = match line.iter().position(|&byte| byte == b':') {
+            Some(colon) => &line[..colon],
+            None => &[][..],
+        };
+        if name.is_empty() || !name.iter().all(|byte| (33..=126).contains(byte)) {
+            problems.push(Problem::MalformedHeaderLine(index + 1));
+            continue;
+        }
+        let name = String::from_utf8_lossy(name).to_ascii_lowercase();
+        if SINGLETON_HEADERS.contains(&name.as_str()) {
+            if seen.contains(&name) {
+                if !problems.contains(&Problem::DuplicateHeader(name.clone())) {
+                    problems.push(Problem::DuplicateHeader(name));
+                }
+            } else {
+                seen.push(name);
+            }
+        }
+    }
+
+    if std::str::from_utf8(&content[..header_end.min(content.len())]).is_err() {
+        problems.push(Problem::InvalidHeaderEncoding);
+    }
+    problems
+}
+
+/// 内容中是否存在前面不是CR的LF
+fn has_bare_line_feed(content: &[u8]) -> bool {
+    content
+        .iter()
+        .enumerate()
+        .any(|(pos, &byte)| byte == b'\n' && (pos == 0 || content[pos - 1] != b'\r'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_problems_are_reported() {
+        let config = Config::default();
+        let content = b"Subject: one\r\nSubject: two\r\nthis line is not a header\r\nTo: broken\r\n\r\nbody\n";
+
+        assert_eq!(
+            check_content(&config, "/mail/bad.eml", content),
+            [
+                Problem::DuplicateHeader("subject".to_string()),
+                Problem::MalformedHeaderLine(3),
+                Problem::BareLineFeed,
+                Problem::MissingFrom,
+                Problem::MissingRecipients,
+            ]
+        );
+
+        let clean = b"From: a@example.com\r\nTo: b@example.com,\r\n c@example.com\r\nSubject: ok\r\n\r\nbody\r\n";
+        assert!(check_content(&config, "/mail/ok.eml", clean).is_empty());
+
+        // 发件人与收件人由配置提供时，邮件头中可以没有对应地址
+        let config = Config {
+            from: Some("sender@example.com".to_string()),
+            to: Some("rcpt@example.com".to_string()),
+            ..Config::default()
+        };
+        assert!(check_content(&config, "/mail/bare.eml", b"Subject: hi\r\n\r\nbody\r\n").is_empty());
+    }
+}
@@ -16,6 +16,7 @@ password = Password
 sender = Sender
 recipient = Recipient
 recipient-hint = (comma separated for multiple)
+bcc = Bcc
 
 ## Send Mode
 send-mode = Send Mode
@@ -63,6 +64,7 @@ failed = Failed
 qps = QPS
 current-round = Current Round
 elapsed-time = Elapsed Time
+eta = ETA
 
 ## Logs
 send-log = Send Log
@@ -16,6 +16,7 @@ password = パスワード
 sender = 送信者
 recipient = 受信者
 recipient-hint = (複数はカンマ区切り)
+bcc = BCC
 
 ## Send Mode
 send-mode = 送信モード
@@ -63,6 +64,7 @@ failed = 失敗
 qps = QPS
 current-round = 現在のラウンド
 elapsed-time = 経過時間
+eta = 残り時間
 
 ## Logs
 send-log = 送信ログ
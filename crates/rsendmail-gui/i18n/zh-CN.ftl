@@ -16,6 +16,7 @@ password = 密码
 sender = 发件人
 recipient = 收件人
 recipient-hint = (多个地址请用逗号分隔)
+bcc = 密送
 
 ## Send Mode
 send-mode = 发送模式
@@ -63,6 +64,7 @@ failed = 失败
 qps = QPS
 current-round = 当前轮次
 elapsed-time = 已用时间
+eta = 预计剩余
 
 ## Logs
 send-log = 发送日志
@@ -16,6 +16,7 @@ password = 密碼
 sender = 寄件人
 recipient = 收件人
 recipient-hint = (多個地址請用逗號分隔)
+bcc = 密件副本
 
 ## Send Mode
 send-mode = 發送模式
@@ -63,6 +64,7 @@ failed = 失敗
 qps = QPS
 current-round = 目前輪次
 elapsed-time = 已用時間
+eta = 預計剩餘
 
 ## Logs
 send-log = 發送日誌
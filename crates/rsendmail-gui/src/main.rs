@@ -1,8 +1,9 @@
 use anyhow::Result;
 use log::{Level, Log, Metadata, Record, SetLoggerError};
-use rsendmail_core::{Config, Mailer, Stats};
+use rsendmail_core::throughput::format_hms;
+use rsendmail_core::{Config, Mailer, RunControl, RunState, SendProgress, Stats};
 use slint::{ComponentHandle, Model, ModelRc, SharedString, VecModel};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
@@ -15,13 +16,69 @@ slint::include_modules!();
 enum SendEvent {
     Log { level: String, message: String },
     Progress { sent: i32, success: i32, fail: i32 },
-    Stats { qps: f32, elapsed: String },
+    Stats { qps: f32, elapsed: String, eta: String },
     RoundStart { current: i32, total: i32 },
     Completed { stats: Stats },
     Stopped,
     Error { message: String },
 }
 
+// 发送过程中把每封邮件的结果以及定期的实时 QPS 与预计剩余时间转发给界面
+struct GuiProgress {
+    tx: mpsc::Sender<SendEvent>,
+    start: Instant,
+    // 本轮已成功、已失败的邮件数，每轮开始时清零
+    success: AtomicUsize,
+    fail: AtomicUsize,
+}
+
+impl GuiProgress {
+    fn new(tx: mpsc::Sender<SendEvent>, start: Instant) -> Self {
+        Self {
+            tx,
+            start,
+            success: AtomicUsize::new(0),
+            fail: AtomicUsize::new(0),
+        }
+    }
+
+    fn reset(&self) {
+        self.success.store(0, Ordering::Relaxed);
+        self.fail.store(0, Ordering::Relaxed);
+    }
+
+    // 通道已满时丢弃本次事件即可，下一次事件携带的是累计数量
+    fn send_counts(&self) {
+        let success = self.success.load(Ordering::Relaxed);
+        let fail = self.fail.load(Ordering::Relaxed);
+        let _ = self.tx.try_send(SendEvent::Progress {
+            sent: (success + fail) as i32,
+            success: success as i32,
+            fail: fail as i32,
+        });
+    }
+}
+
+impl SendProgress for GuiProgress {
+    fn on_email_sent(&self, _path: &str, _duration: Duration) {
+        self.success.fetch_add(1, Ordering::Relaxed);
+        self.send_counts();
+    }
+
+    fn on_email_failed(&self, _path: &str, _error: &str) {
+        self.fail.fetch_add(1, Ordering::Relaxed);
+        self.send_counts();
+    }
+
+    fn on_throughput(&self, qps: f64, eta: Option<Duration>) {
+        let _ = self.tx.try_send(SendEvent::Stats {
+            qps: qps as f32,
+            elapsed: format_hms(self.start.elapsed()),
+            eta: eta.map_or_else(|| "--:--:--".to_string(), format_hms),
+        });
+    }
+}
+
 // 自定义 Logger，同时输出到终端和 GUI
 struct GuiLogger {
     tx: Mutex<Option<tokio::sync::mpsc::Sender<SendEvent>>>,
@@ -94,8 +151,8 @@ fn main() -> Result<()> {
     // 初始化 i18n
     setup_i18n(&app);
 
-    // 创建用于取消发送的标志
-    let running = Arc::new(AtomicBool::new(false));
+    // 创建发送任务的运行状态（停止按钮先排空，再次点击中止）
+    let running = RunControl::new();
 
     // 设置回调
     setup_callbacks(&app, running.clone());
@@ -107,6 +164,8 @@ fn main() -> Result<()> {
 }
 
 fn setup_i18n(app: &AppWindow) {
+    i18n::init();
+
     // 设置语言列表
     let languages: Vec<SharedString> = i18n::language_names()
         .into_iter()
@@ -135,70 +194,72 @@ fn setup_i18n(app: &AppWindow) {
 
 fn update_ui_texts(app: &AppWindow) {
     // 更新所有 UI 文本
-    app.set_tr_smtp_server(i18n::t("smtp-server").into());
-    app.set_tr_server_address(i18n::t("server-address").into());
+    app.set_tr_smtp_server(i18n::t("smtp_server").into());
+    app.set_tr_server_address(i18n::t("server_address").into());
     app.set_tr_port(i18n::t("port").into());
-    app.set_tr_use_tls(i18n::t("use-tls").into());
-    app.set_tr_accept_invalid_certs(i18n::t("accept-invalid-certs").into());
-    app.set_tr_auth_required(i18n::t("auth-required").into());
+    app.set_tr_use_tls(i18n::t("use_tls").into());
+    app.set_tr_accept_invalid_certs(i18n::t("accept_invalid_certs").into());
+    app.set_tr_auth_required(i18n::t("auth_required").into());
     app.set_tr_username(i18n::t("username").into());
     app.set_tr_password(i18n::t("password").into());
     app.set_tr_sender(i18n::t("sender").into());
     app.set_tr_recipient(i18n::t("recipient").into());
-    app.set_tr_recipient_hint(i18n::t("recipient-hint").into());
-
-    app.set_tr_send_mode(i18n::t("send-mode").into());
-    app.set_tr_eml_batch(i18n::t("eml-batch").into());
-    app.set_tr_single_attachment(i18n::t("single-attachment").into());
-    app.set_tr_dir_attachment(i18n::t("dir-attachment").into());
-    app.set_tr_eml_directory(i18n::t("eml-directory").into());
-    app.set_tr_attachment_file(i18n::t("attachment-file").into());
-    app.set_tr_attachment_directory(i18n::t("attachment-directory").into());
+    app.set_tr_recipient_hint(i18n::t("recipient_hint").into());
+    app.set_tr_bcc(i18n::t("bcc").into());
+
+    app.set_tr_send_mode(i18n::t("send_mode").into());
+    app.set_tr_eml_batch(i18n::t("eml_batch").into());
+    app.set_tr_single_attachment(i18n::t("single_attachment").into());
+    app.set_tr_dir_attachment(i18n::t("dir_attachment").into());
+    app.set_tr_eml_directory(i18n::t("eml_directory").into());
+    app.set_tr_attachment_file(i18n::t("attachment_file").into());
+    app.set_tr_attachment_directory(i18n::t("attachment_directory").into());
     app.set_tr_extension(i18n::t("extension").into());
     app.set_tr_browse(i18n::t("browse").into());
-    app.set_tr_email_subject(i18n::t("email-subject").into());
-    app.set_tr_email_body(i18n::t("email-body").into());
-    app.set_tr_filename_hint(i18n::t("filename-hint").into());
+    app.set_tr_email_subject(i18n::t("email_subject").into());
+    app.set_tr_email_body(i18n::t("email_body").into());
+    app.set_tr_filename_hint(i18n::t("filename_hint").into());
 
-    app.set_tr_advanced_options(i18n::t("advanced-options").into());
+    app.set_tr_advanced_options(i18n::t("advanced_options").into());
     app.set_tr_performance(i18n::t("performance").into());
     app.set_tr_processes(i18n::t("processes").into());
-    app.set_tr_batch_size(i18n::t("batch-size").into());
-    app.set_tr_send_interval(i18n::t("send-interval").into());
+    app.set_tr_batch_size(i18n::t("batch_size").into());
+    app.set_tr_send_interval(i18n::t("send_interval").into());
     app.set_tr_timeout(i18n::t("timeout").into());
-    app.set_tr_loop_settings(i18n::t("loop-settings").into());
-    app.set_tr_infinite_loop(i18n::t("infinite-loop").into());
-    app.set_tr_repeat_count(i18n::t("repeat-count").into());
-    app.set_tr_loop_interval(i18n::t("loop-interval").into());
-    app.set_tr_retry_interval(i18n::t("retry-interval").into());
-    app.set_tr_email_processing(i18n::t("email-processing").into());
-    app.set_tr_keep_headers(i18n::t("keep-headers").into());
-    app.set_tr_modify_headers(i18n::t("modify-headers").into());
-    app.set_tr_envelope_cc_bcc(i18n::t("envelope-cc-bcc").into());
-    app.set_tr_anonymize_emails(i18n::t("anonymize-emails").into());
+    app.set_tr_loop_settings(i18n::t("loop_settings").into());
+    app.set_tr_infinite_loop(i18n::t("infinite_loop").into());
+    app.set_tr_repeat_count(i18n::t("repeat_count").into());
+    app.set_tr_loop_interval(i18n::t("loop_interval").into());
+    app.set_tr_retry_interval(i18n::t("retry_interval").into());
+    app.set_tr_email_processing(i18n::t("email_processing").into());
+    app.set_tr_keep_headers(i18n::t("keep_headers").into());
+    app.set_tr_modify_headers(i18n::t("modify_headers").into());
+    app.set_tr_envelope_cc_bcc(i18n::t("envelope_cc_bcc").into());
+    app.set_tr_anonymize_emails(i18n::t("anonymize_emails").into());
     app.set_tr_domain(i18n::t("domain").into());
     app.set_tr_logging(i18n::t("logging").into());
-    app.set_tr_log_level(i18n::t("log-level").into());
-    app.set_tr_log_file(i18n::t("log-file").into());
-    app.set_tr_failed_emails_dir(i18n::t("failed-emails-dir").into());
+    app.set_tr_log_level(i18n::t("log_level").into());
+    app.set_tr_log_file(i18n::t("log_file").into());
+    app.set_tr_failed_emails_dir(i18n::t("failed_emails_dir").into());
     app.set_tr_optional(i18n::t("optional").into());
 
     app.set_tr_statistics(i18n::t("statistics").into());
     app.set_tr_total(i18n::t("total").into());
     app.set_tr_success(i18n::t("success").into());
     app.set_tr_failed(i18n::t("failed").into());
-    app.set_tr_current_round(i18n::t("current-round").into());
-    app.set_tr_elapsed_time(i18n::t("elapsed-time").into());
+    app.set_tr_current_round(i18n::t("current_round").into());
+    app.set_tr_elapsed_time(i18n::t("elapsed_time").into());
+    app.set_tr_eta(i18n::t("eta").into());
 
-    app.set_tr_send_log(i18n::t("send-log").into());
+    app.set_tr_send_log(i18n::t("send_log").into());
     app.set_tr_clear(i18n::t("clear").into());
-    app.set_tr_export_log(i18n::t("export-log").into());
+    app.set_tr_export_log(i18n::t("export_log").into());
 
-    app.set_tr_save_config(i18n::t("save-config").into());
-    app.set_tr_load_config(i18n::t("load-config").into());
-    app.set_tr_test_connection(i18n::t("test-connection").into());
-    app.set_tr_start_send(i18n::t("start-send").into());
-    app.set_tr_stop_send(i18n::t("stop-send").into());
+    app.set_tr_save_config(i18n::t("save_config").into());
+    app.set_tr_load_config(i18n::t("load_config").into());
+    app.set_tr_test_connection(i18n::t("test_connection").into());
+    app.set_tr_start_send(i18n::t("start_send").into());
+    app.set_tr_stop_send(i18n::t("stop_send").into());
 
     app.set_tr_language(i18n::t("language").into());
     app.set_tr_theme(i18n::t("theme").into());
@@ -211,16 +272,16 @@ fn update_ui_texts(app: &AppWindow) {
 fn update_status_text(app: &AppWindow) {
     let status = app.get_status();
     let text = match status {
-        SendStatus::Idle => i18n::t("status-ready"),
-        SendStatus::Preparing => i18n::t("status-preparing"),
-        SendStatus::Sending => i18n::t("status-sending"),
-        SendStatus::Stopped => i18n::t("status-stopped"),
-        SendStatus::Completed => i18n::t("status-completed"),
+        SendStatus::Idle => i18n::t("status_ready"),
+        SendStatus::Preparing => i18n::t("status_preparing"),
+        SendStatus::Sending => i18n::t("status_sending"),
+        SendStatus::Stopped => i18n::t("status_stopped"),
+        SendStatus::Completed => i18n::t("status_completed"),
     };
     app.set_status_text(text.into());
 }
 
-fn setup_callbacks(app: &AppWindow, running: Arc<AtomicBool>) {
+fn setup_callbacks(app: &AppWindow, running: RunControl) {
     let app_weak = app.as_weak();
 
     // 关闭消息对话框
@@ -237,31 +298,51 @@ fn setup_callbacks(app: &AppWindow, running: Arc<AtomicBool>) {
         let app_weak = app_weak.clone();
         app.on_test_connection(move || {
             let app = app_weak.unwrap();
-            add_log(&app, "INFO", &i18n::t("status-preparing"));
+            add_log(&app, "INFO", &i18n::t("status_preparing"));
 
             let config = build_config_from_ui(&app);
 
             // 验证必填字段
             if config.smtp_server.is_empty() {
-                show_error(&app, &i18n::t("error-no-smtp-server"));
+                show_error(&app, &i18n::t("error_no_smtp_server"));
                 return;
             }
             if config.from.as_ref().map_or(true, |s| s.is_empty()) {
-                show_error(&app, &i18n::t("error-no-sender"));
+                show_error(&app, &i18n::t("error_no_sender"));
                 return;
             }
 
             add_log(
                 &app,
                 "INFO",
-                &format!(
-                    "连接到 {}:{} (TLS: {})",
-                    config.smtp_server, config.port, config.use_tls
+                &i18n::t_with_args(
+                    "connect_to",
+                    &[
+                        ("server", &config.smtp_server),
+                        ("port", &config.port.to_string()),
+                        ("tls", &config.use_tls.to_string()),
+                    ],
                 ),
             );
 
-            // TODO: 实际测试连接逻辑
-            add_log(&app, "INFO", "连接测试功能待实现");
+            // 在后台线程执行连接测试，结果回到UI线程显示
+            let app_weak = app_weak.clone();
+            std::thread::spawn(move || {
+                let rt = tokio::runtime::Runtime::new().unwrap();
+                let result = rt.block_on(Mailer::new(config).test_connection());
+                let _ = app_weak.upgrade_in_event_loop(move |app| match result {
+                    Ok(report) => {
+                        for line in report.lines() {
+                            add_log(&app, "INFO", line);
+                        }
+                        show_message(&app, &i18n::t("test_connection"), &report);
+                    }
+                    Err(e) => {
+                        add_log(&app, "ERROR", &e.to_string());
+                        show_error(&app, &e.to_string());
+                    }
+                });
+            });
         });
     }
 
@@ -282,13 +363,13 @@ fn setup_callbacks(app: &AppWindow, running: Arc<AtomicBool>) {
 
             // 更新状态
             app.set_status(SendStatus::Preparing);
-            app.set_status_text("准备中...".into());
+            app.set_status_text(i18n::t("status_preparing").into());
             app.set_sent_count(0);
             app.set_success_count(0);
             app.set_fail_count(0);
 
-            // 设置 running 标志
-            running.store(true, Ordering::SeqCst);
+            // 恢复运行状态
+            running.reset();
 
             // 创建通道
             let (tx, mut rx) = mpsc::channel::<SendEvent>(100);
@@ -329,41 +410,44 @@ fn setup_callbacks(app: &AppWindow, running: Arc<AtomicBool>) {
                                 app.set_success_count(success);
                                 app.set_fail_count(fail);
                             }
-                            SendEvent::Stats { qps, elapsed } => {
+                            SendEvent::Stats { qps, elapsed, eta } => {
                                 app.set_qps(qps);
                                 app.set_elapsed_time(elapsed.into());
+                                app.set_eta(eta.into());
                             }
                             SendEvent::RoundStart { current, total } => {
                                 app.set_current_round(current);
                                 app.set_total_rounds(total);
                                 app.set_status(SendStatus::Sending);
-                                app.set_status_text("发送中...".into());
+                                app.set_status_text(i18n::t("status_sending").into());
                             }
                             SendEvent::Completed { stats } => {
                                 app.set_status(SendStatus::Completed);
-                                app.set_status_text("完成".into());
-                                app.set_total_count(stats.email_count as i32);
-                                running_for_events.store(false, Ordering::SeqCst);
+                                app.set_status_text(i18n::t("status_completed").into());
+                                app.set_total_count(stats.total_count() as i32);
+                                running_for_events.drain();
                                 add_log(
                                     &app,
                                     "INFO",
-                                    &format!(
-                                        "发送完成！成功: {}, 失败: {}",
-                                        stats.email_count - stats.send_errors - stats.parse_errors,
-                                        stats.send_errors + stats.parse_errors
+                                    &i18n::t_with_args(
+                                        "send_complete",
+                                        &[
+                                            ("success", &stats.success_count().to_string()),
+                                            ("failed", &stats.failed_count().to_string()),
+                                        ],
                                     ),
                                 );
                             }
                             SendEvent::Stopped => {
                                 app.set_status(SendStatus::Stopped);
-                                app.set_status_text("已停止".into());
-                                running_for_events.store(false, Ordering::SeqCst);
+                                app.set_status_text(i18n::t("status_stopped").into());
+                                running_for_events.drain();
                             }
                             SendEvent::Error { message } => {
                                 add_log(&app, "ERROR", &message);
                                 app.set_status(SendStatus::Stopped);
-                                app.set_status_text("错误".into());
-                                running_for_events.store(false, Ordering::SeqCst);
+                                app.set_status_text(i18n::t("error_title").into());
+                                running_for_events.drain();
                             }
                         }
                     }
@@ -379,9 +463,17 @@ fn setup_callbacks(app: &AppWindow, running: Arc<AtomicBool>) {
         let running = running.clone();
         app.on_stop_send(move || {
             let app = app_weak.unwrap();
-            add_log(&app, "WARN", "正在停止发送...");
-            running.store(false, Ordering::SeqCst);
-            app.set_status_text("停止中...".into());
+            // 第一次点击：完成正在发送的邮件后停止；再次点击：立即中止
+            match running.request_stop() {
+                RunState::Draining => {
+                    add_log(&app, "WARN", &i18n::t("stopping"));
+                    app.set_status_text(i18n::t("status_stopping").into());
+                }
+                _ => {
+                    add_log(&app, "WARN", &i18n::t("aborting"));
+                    app.set_status_text(i18n::t("status_aborting").into());
+                }
+            }
         });
     }
 
@@ -483,9 +575,9 @@ fn setup_callbacks(app: &AppWindow, running: Arc<AtomicBool>) {
                     }
                 }
                 if let Err(e) = std::fs::write(&path, content) {
-                    add_log(&app, "ERROR", &format!("导出日志失败: {}", e));
+                    add_log(&app, "ERROR", &i18n::t_with_args("export_log_failed", &[("error", &e.to_string())]));
                 } else {
-                    add_log(&app, "INFO", &format!("日志已导出到: {}", path.display()));
+                    add_log(&app, "INFO", &i18n::t_with_args("log_exported", &[("path", &path.display().to_string())]));
                 }
             }
         });
@@ -504,13 +596,13 @@ fn setup_callbacks(app: &AppWindow, running: Arc<AtomicBool>) {
                 match serde_json::to_string_pretty(&config) {
                     Ok(json) => {
                         if let Err(e) = std::fs::write(&path, json) {
-                            add_log(&app, "ERROR", &format!("保存配置失败: {}", e));
+                            add_log(&app, "ERROR", &i18n::t_with_args("save_config_failed", &[("error", &e.to_string())]));
                         } else {
-                            add_log(&app, "INFO", &format!("配置已保存到: {}", path.display()));
+                            add_log(&app, "INFO", &i18n::t_with_args("config_saved", &[("path", &path.display().to_string())]));
                         }
                     }
                     Err(e) => {
-                        add_log(&app, "ERROR", &format!("序列化配置失败: {}", e));
+                        add_log(&app, "ERROR", &i18n::t_with_args("serialize_config_failed", &[("error", &e.to_string())]));
                     }
                 }
             }
@@ -530,14 +622,14 @@ fn setup_callbacks(app: &AppWindow, running: Arc<AtomicBool>) {
                     Ok(content) => match serde_json::from_str::<Config>(&content) {
                         Ok(config) => {
                             apply_config_to_ui(&app, &config);
-                            add_log(&app, "INFO", &format!("配置已加载: {}", path.display()));
+                            add_log(&app, "INFO", &i18n::t_with_args("config_loaded", &[("path", &path.display().to_string())]));
                         }
                         Err(e) => {
-                            add_log(&app, "ERROR", &format!("解析配置失败: {}", e));
+                            add_log(&app, "ERROR", &i18n::t_with_args("parse_config_failed", &[("error", &e.to_string())]));
                         }
                     },
                     Err(e) => {
-                        add_log(&app, "ERROR", &format!("读取配置失败: {}", e));
+                        add_log(&app, "ERROR", &i18n::t_with_args("read_config_failed", &[("error", &e.to_string())]));
                     }
                 }
             }
@@ -576,13 +668,12 @@ fn add_log(app: &AppWindow, level: &str, message: &str) {
 }
 
 fn show_error(app: &AppWindow, message: &str) {
-    app.set_message_dialog_title(i18n::t("error-title").into());
+    app.set_message_dialog_title(i18n::t("error_title").into());
     app.set_message_dialog_content(message.into());
     app.set_message_dialog_is_error(true);
     app.set_show_message_dialog(true);
 }
 
-#[allow(dead_code)]
 fn show_message(app: &AppWindow, title: &str, message: &str) {
     app.set_message_dialog_title(title.into());
     app.set_message_dialog_content(message.into());
@@ -638,6 +729,7 @@ fn build_config_from_ui(app: &AppWindow) -> Config {
         port: parse_u16(app.get_smtp_port_str().as_ref(), 25),
         from: non_empty(app.get_from_address().to_string()),
         to: non_empty(app.get_to_address().to_string()),
+        bcc: non_empty(app.get_bcc_address().to_string()),
         envelope_cc_bcc: app.get_envelope_cc_bcc(),
         dir,
         extension: app.get_eml_extension().to_string(),
@@ -686,6 +778,8 @@ fn build_config_from_ui(app: &AppWindow) -> Config {
         } else {
             Some(log_file)
         },
+        // Options not exposed in the GUI keep their defaults
+        ..Config::default()
     }
 }
 
@@ -694,6 +788,7 @@ fn apply_config_to_ui(app: &AppWindow, config: &Config) {
     app.set_smtp_port_str(config.port.to_string().into());
     app.set_from_address(config.from.clone().unwrap_or_default().into());
     app.set_to_address(config.to.clone().unwrap_or_default().into());
+    app.set_bcc_address(config.bcc.clone().unwrap_or_default().into());
     app.set_use_tls(config.use_tls);
     app.set_accept_invalid_certs(config.accept_invalid_certs);
     app.set_auth_mode(config.auth_mode);
@@ -752,7 +847,7 @@ fn apply_config_to_ui(app: &AppWindow, config: &Config) {
 
 fn validate_config(config: &Config, app: &AppWindow) -> Result<(), String> {
     if config.smtp_server.is_empty() {
-        return Err(i18n::t("error-no-smtp-server"));
+        return Err(i18n::t("error_no_smtp_server"));
     }
     let send_mode = app.get_send_mode();
 
@@ -760,37 +855,37 @@ fn validate_config(config: &Config, app: &AppWindow) -> Result<(), String> {
     let is_eml_mode = matches!(send_mode, SendMode::EmlBatch);
     if !is_eml_mode {
         if config.from.as_ref().map_or(true, |s| s.is_empty()) {
-            return Err(i18n::t("error-no-sender"));
+            return Err(i18n::t("error_no_sender"));
         }
         if config.to.as_ref().map_or(true, |s| s.is_empty()) {
-            return Err(i18n::t("error-no-recipient"));
+            return Err(i18n::t("error_no_recipient"));
         }
     }
 
     match send_mode {
         SendMode::EmlBatch => {
             if config.dir.is_none() {
-                return Err(i18n::t("error-no-eml-dir"));
+                return Err(i18n::t("error_no_eml_dir"));
             }
         }
         SendMode::SingleAttachment => {
             if config.attachment.is_none() {
-                return Err(i18n::t("error-no-attachment"));
+                return Err(i18n::t("error_no_attachment"));
             }
         }
         SendMode::DirAttachment => {
             if config.attachment_dir.is_none() {
-                return Err(i18n::t("error-no-attachment-dir"));
+                return Err(i18n::t("error_no_attachment_dir"));
             }
         }
     }
 
     if config.auth_mode {
         if config.username.as_ref().map_or(true, |s| s.is_empty()) {
-            return Err(i18n::t("error-no-username"));
+            return Err(i18n::t("error_no_username"));
         }
         if config.password.as_ref().map_or(true, |s| s.is_empty()) {
-            return Err(i18n::t("error-no-password"));
+            return Err(i18n::t("error_no_password"));
         }
     }
 
@@ -818,8 +913,10 @@ fn count_all_files(dir: &str) -> i32 {
         .count() as i32
 }
 
-async fn run_send_task(config: Config, running: Arc<AtomicBool>, tx: mpsc::Sender<SendEvent>) {
-    let mailer = Mailer::new(config.clone());
+async fn run_send_task(config: Config, running: RunControl, tx: mpsc::Sender<SendEvent>) {
+    let start_time = Instant::now();
+    let progress = Arc::new(GuiProgress::new(tx.clone(), start_time));
+    let mailer = Mailer::new_with_progress(config.clone(), progress.clone());
 
     let total_rounds = if config.r#loop {
         i32::MAX
@@ -828,9 +925,9 @@ async fn run_send_task(config: Config, running: Arc<AtomicBool>, tx: mpsc::Sende
     };
 
     let mut current_round = 1;
-    let start_time = Instant::now();
 
-    while current_round <= total_rounds && running.load(Ordering::SeqCst) {
+    while current_round <= total_rounds && running.is_running() {
+        progress.reset();
         let _ = tx
             .send(SendEvent::RoundStart {
                 current: current_round,
@@ -841,14 +938,19 @@ async fn run_send_task(config: Config, running: Arc<AtomicBool>, tx: mpsc::Sende
         let _ = tx
             .send(SendEvent::Log {
                 level: "INFO".to_string(),
-                message: format!(
-                    "开始第 {}/{} 轮发送",
-                    current_round,
-                    if config.r#loop {
-                        "∞".to_string()
-                    } else {
-                        total_rounds.to_string()
-                    }
+                message: i18n::t_with_args(
+                    "round_start",
+                    &[
+                        ("current", &current_round.to_string()),
+                        (
+                            "total",
+                            &if config.r#loop {
+                                "∞".to_string()
+                            } else {
+                                total_rounds.to_string()
+                            },
+                        ),
+                    ],
                 ),
             })
             .await;
@@ -856,20 +958,10 @@ async fn run_send_task(config: Config, running: Arc<AtomicBool>, tx: mpsc::Sende
         match mailer.send_all_with_cancel(running.clone()).await {
             Ok(stats) => {
                 let elapsed = start_time.elapsed();
-                let elapsed_str = format!(
-                    "{:02}:{:02}:{:02}",
-                    elapsed.as_secs() / 3600,
-                    (elapsed.as_secs() % 3600) / 60,
-                    elapsed.as_secs() % 60
-                );
-
-                let total_errors = stats.send_errors + stats.parse_errors;
-                let success = if stats.email_count > total_errors {
-                    stats.email_count - total_errors
-                } else {
-                    0
-                };
-                let fail = total_errors;
+                let elapsed_str = format_hms(elapsed);
+
+                let success = stats.success_count();
+                let fail = stats.failed_count();
                 let qps = if elapsed.as_secs_f32() > 0.0 {
                     stats.email_count as f32 / elapsed.as_secs_f32()
                 } else {
@@ -878,7 +970,7 @@ async fn run_send_task(config: Config, running: Arc<AtomicBool>, tx: mpsc::Sende
 
                 let _ = tx
                     .send(SendEvent::Progress {
-                        sent: stats.email_count as i32,
+                        sent: stats.total_count() as i32,
                         success: success as i32,
                         fail: fail as i32,
                     })
@@ -888,29 +980,36 @@ async fn run_send_task(config: Config, running: Arc<AtomicBool>, tx: mpsc::Sende
                     .send(SendEvent::Stats {
                         qps,
                         elapsed: elapsed_str,
+                        eta: format_hms(Duration::ZERO),
                     })
                     .await;
 
                 let _ = tx
                     .send(SendEvent::Log {
                         level: "INFO".to_string(),
-                        message: format!("第 {} 轮发送完成", current_round),
+                        message: i18n::t_with_args(
+                            "round_complete",
+                            &[("round", &current_round.to_string())],
+                        ),
                     })
                     .await;
 
                 // 检查是否需要继续
-                if current_round < total_rounds && running.load(Ordering::SeqCst) {
+                if current_round < total_rounds && running.is_running() {
                     let _ = tx
                         .send(SendEvent::Log {
                             level: "INFO".to_string(),
-                            message: format!("等待 {} 秒后开始下一轮...", config.loop_interval),
+                            message: i18n::t_with_args(
+                                "wait_next_round",
+                                &[("seconds", &config.loop_interval.to_string())],
+                            ),
                         })
                         .await;
                     tokio::time::sleep(Duration::from_secs(config.loop_interval)).await;
                 }
 
                 // 最后一轮完成
-                if current_round >= total_rounds || !running.load(Ordering::SeqCst) {
+                if current_round >= total_rounds || !running.is_running() {
                     let _ = tx.send(SendEvent::Completed { stats }).await;
                     break;
                 }
@@ -918,7 +1017,7 @@ async fn run_send_task(config: Config, running: Arc<AtomicBool>, tx: mpsc::Sende
             Err(e) => {
                 let _ = tx
                     .send(SendEvent::Error {
-                        message: format!("发送失败: {}", e),
+                        message: i18n::t_with_args("send_failed", &[("error", &e.to_string())]),
                     })
                     .await;
                 break;
@@ -928,7 +1027,7 @@ async fn run_send_task(config: Config, running: Arc<AtomicBool>, tx: mpsc::Sende
         current_round += 1;
     }
 
-    if !running.load(Ordering::SeqCst) {
+    if !running.is_running() {
         let _ = tx.send(SendEvent::Stopped).await;
     }
 }
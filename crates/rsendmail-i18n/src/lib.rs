@@ -8,6 +8,8 @@
 //! - Simplified Chinese (zh-CN)
 //! - Traditional Chinese (zh-TW)
 //! - Japanese (ja-JP)
+//! - Korean (ko-KR)
+//! - German (de-DE)
 //!
 //! # Usage
 //! ```rust,ignore
@@ -30,6 +32,8 @@ pub enum Language {
     SimplifiedChinese,
     TraditionalChinese,
     Japanese,
+    Korean,
+    German,
 }
 
 impl Language {
@@ -40,6 +44,8 @@ impl Language {
             Language::SimplifiedChinese => "zh-CN",
             Language::TraditionalChinese => "zh-TW",
             Language::Japanese => "ja-JP",
+            Language::Korean => "ko-KR",
+            Language::German => "de-DE",
         }
     }
 
@@ -50,6 +56,8 @@ impl Language {
             Language::SimplifiedChinese => "简体中文",
             Language::TraditionalChinese => "繁體中文",
             Language::Japanese => "日本語",
+            Language::Korean => "한국어",
+            Language::German => "Deutsch",
         }
     }
 
@@ -60,6 +68,8 @@ impl Language {
             Language::SimplifiedChinese => "zh-CN",
             Language::TraditionalChinese => "zh-TW",
             Language::Japanese => "ja",
+            Language::Korean => "ko",
+            Language::German => "de",
         }
     }
 
@@ -71,6 +81,8 @@ impl Language {
             "zh-cn" | "zh_cn" | "zh-hans" | "zh" | "chinese" => Some(Language::SimplifiedChinese),
             "zh-tw" | "zh_tw" | "zh-hant" | "zh-hk" | "zh_hk" => Some(Language::TraditionalChinese),
             "ja" | "ja-jp" | "ja_jp" | "japanese" => Some(Language::Japanese),
+            "ko" | "ko-kr" | "ko_kr" | "korean" => Some(Language::Korean),
+            "de" | "de-de" | "de_de" | "german" => Some(Language::German),
             _ => None,
         }
     }
@@ -126,6 +138,10 @@ impl Language {
             Some(Language::TraditionalChinese)
         } else if s.starts_with("ja") {
             Some(Language::Japanese)
+        } else if s.starts_with("ko") {
+            Some(Language::Korean)
+        } else if s.starts_with("de") {
+            Some(Language::German)
         } else if s.starts_with("en") {
             Some(Language::English)
         } else {
@@ -140,6 +156,8 @@ impl Language {
             1 => Language::SimplifiedChinese,
             2 => Language::TraditionalChinese,
             3 => Language::Japanese,
+            4 => Language::Korean,
+            5 => Language::German,
             _ => Language::English,
         }
     }
@@ -151,6 +169,8 @@ impl Language {
             Language::SimplifiedChinese => 1,
             Language::TraditionalChinese => 2,
             Language::Japanese => 3,
+            Language::Korean => 4,
+            Language::German => 5,
         }
     }
 
@@ -161,6 +181,8 @@ impl Language {
             Language::SimplifiedChinese,
             Language::TraditionalChinese,
             Language::Japanese,
+            Language::Korean,
+            Language::German,
         ]
     }
 
@@ -168,6 +190,22 @@ impl Language {
     pub fn all_names() -> Vec<&'static str> {
         Self::all().iter().map(|l| l.name()).collect()
     }
+
+    /// Plural categories this language distinguishes (CLDR names)
+    fn plural_categories(&self) -> &'static [&'static str] {
+        match self {
+            Language::English | Language::German => &["one", "other"],
+            _ => &["other"],
+        }
+    }
+
+    /// Plural category for `count` items
+    fn plural_category(&self, count: u64) -> &'static str {
+        match self {
+            Language::English | Language::German if count == 1 => "one",
+            _ => "other",
+        }
+    }
 }
 
 impl Default for Language {
@@ -182,6 +220,69 @@ impl std::fmt::Display for Language {
     }
 }
 
+/// Raw locale file for a language, embedded so translation coverage can be checked at runtime
+fn locale_source(lang: Language) -> &'static str {
+    match lang {
+        Language::English => include_str!("../locales/en-US.yml"),
+        Language::SimplifiedChinese => include_str!("../locales/zh-CN.yml"),
+        Language::TraditionalChinese => include_str!("../locales/zh-TW.yml"),
+        Language::Japanese => include_str!("../locales/ja-JP.yml"),
+        Language::Korean => include_str!("../locales/ko-KR.yml"),
+        Language::German => include_str!("../locales/de-DE.yml"),
+    }
+}
+
+/// Flatten the nested keys of a locale file into dotted paths (e.g. `core.mailer.email_send_success`)
+///
+/// Only the subset of YAML used by the locale files is understood: indented mappings whose
+/// leaves are single-line values, plus comments and blank lines.
+fn translation_keys(source: &str) -> Vec<String> {
+    let mut parents: Vec<(usize, &str)> = Vec::new();
+    let mut keys = Vec::new();
+    for line in source.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = trimmed.split_once(':') else {
+            continue;
+        };
+        let indent = line.len() - trimmed.len();
+        while parents.last().is_some_and(|(depth, _)| *depth >= indent) {
+            parents.pop();
+        }
+        if value.trim().is_empty() {
+            parents.push((indent, key));
+        } else {
+            let path: Vec<&str> = parents.iter().map(|(_, parent)| *parent).collect();
+            keys.push([path.as_slice(), &[key]].concat().join("."));
+        }
+    }
+    keys
+}
+
+/// Keys translated in English but missing from `lang`, in English file order
+///
+/// Missing keys silently fall back to English at runtime, so this is the only way to
+/// notice an untranslated string.
+///
+/// Plural variants (`one`, `other`) are only required for the categories `lang` uses,
+/// so a language without a singular form need not repeat the `other` text under `one`.
+pub fn missing_keys(lang: Language) -> Vec<String> {
+    let present: std::collections::HashSet<String> =
+        translation_keys(locale_source(lang)).into_iter().collect();
+    translation_keys(locale_source(Language::English))
+        .into_iter()
+        .filter(|key| !present.contains(key))
+        .filter(|key| match key.rsplit_once('.') {
+            Some((_, category)) if PLURAL_CATEGORIES.contains(&category) => {
+                lang.plural_categories().contains(&category)
+            }
+            _ => true,
+        })
+        .collect()
+}
+
 /// Set the current language for all translations
 pub fn set_language(lang: Language) {
     rust_i18n::set_locale(lang.locale_code());
@@ -194,6 +295,8 @@ pub fn current_language() -> Language {
         "zh-CN" => Language::SimplifiedChinese,
         "zh-TW" => Language::TraditionalChinese,
         "ja-JP" => Language::Japanese,
+        "ko-KR" => Language::Korean,
+        "de-DE" => Language::German,
         _ => Language::English,
     }
 }
@@ -222,6 +325,20 @@ pub fn tr_with_args(key: &str, args: &[(&str, &str)]) -> String {
     result
 }
 
+/// Plural categories that may appear as the last segment of a plural key
+const PLURAL_CATEGORIES: &[&str] = &["one", "other"];
+
+/// Translate a plural key, picking the `one`/`other` variant the current language uses for `count`
+///
+/// `%{count}` is filled in from `count`; `args` supplies any other placeholders.
+pub fn tr_plural(key: &str, count: u64, args: &[(&str, &str)]) -> String {
+    let category = current_language().plural_category(count);
+    let count = count.to_string();
+    let mut args = args.to_vec();
+    args.push(("count", &count));
+    tr_with_args(&format!("{}.{}", key, category), &args)
+}
+
 // Re-export for crates that want to use the macro directly
 // Note: Using t! from other crates requires i18n! to be called in that crate too
 pub use rust_i18n::t;
@@ -236,9 +353,21 @@ mod tests {
         assert_eq!(Language::from_str("zh-CN"), Some(Language::SimplifiedChinese));
         assert_eq!(Language::from_str("zh-TW"), Some(Language::TraditionalChinese));
         assert_eq!(Language::from_str("ja"), Some(Language::Japanese));
+        assert_eq!(Language::from_str("ko"), Some(Language::Korean));
+        assert_eq!(Language::from_str("de-DE"), Some(Language::German));
         assert_eq!(Language::from_str("unknown"), None);
     }
 
+    #[test]
+    fn test_language_from_locale_string() {
+        assert_eq!(Language::from_locale_string("zh_CN.UTF-8"), Some(Language::SimplifiedChinese));
+        assert_eq!(Language::from_locale_string("zh-TW"), Some(Language::TraditionalChinese));
+        assert_eq!(Language::from_locale_string("ja_JP"), Some(Language::Japanese));
+        assert_eq!(Language::from_locale_string("en_US"), Some(Language::English));
+        assert_eq!(Language::from_locale_string("ko_KR.UTF-8"), Some(Language::Korean));
+        assert_eq!(Language::from_locale_string("de_DE"), Some(Language::German));
+    }
+
     #[test]
     fn test_language_locale_code() {
         assert_eq!(Language::English.locale_code(), "en-US");
@@ -247,11 +376,33 @@ mod tests {
         assert_eq!(Language::Japanese.locale_code(), "ja-JP");
     }
 
+    #[test]
+    fn test_every_language_has_all_english_keys() {
+        let keys = translation_keys("cli:\n  # comment\n  port: \"Port: 25\"\ncore:\n  stats:\n    title: \"T\"\n  top: \"x\"\n");
+        assert_eq!(keys, ["cli.port", "core.stats.title", "core.top"]);
+        assert!(translation_keys(locale_source(Language::English)).contains(&"cli.smtp_server".to_string()));
+
+        for lang in Language::all() {
+            assert_eq!(missing_keys(*lang), Vec::<String>::new(), "{} is missing translations", lang.locale_code());
+        }
+    }
+
+    #[test]
+    fn test_tr_plural_english() {
+        set_language(Language::English);
+        assert_eq!(tr_plural("core.stats.success_sent", 0, &[]), "    Successfully sent: 0 emails");
+        assert_eq!(tr_plural("core.stats.success_sent", 1, &[]), "    Successfully sent: 1 email");
+        assert_eq!(tr_plural("core.stats.success_sent", 5, &[]), "    Successfully sent: 5 emails");
+        assert_eq!(Language::Japanese.plural_category(1), "other");
+    }
+
     #[test]
     fn test_language_index() {
         assert_eq!(Language::English.index(), 0);
         assert_eq!(Language::SimplifiedChinese.index(), 1);
         assert_eq!(Language::from_index(2), Language::TraditionalChinese);
         assert_eq!(Language::from_index(3), Language::Japanese);
+        assert_eq!(Language::from_index(4), Language::Korean);
+        assert_eq!(Language::German.index(), 5);
     }
 }
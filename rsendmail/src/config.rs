@@ -1,5 +1,8 @@
 use clap::Parser;
 use log::LevelFilter;
+use std::time::Duration;
+
+use crate::backoff;
 
 /// A high-performance bulk email sending CLI tool
 #[derive(Parser, Debug, Clone)]
@@ -77,6 +80,14 @@ pub struct Config {
     #[arg(long, default_value_t = 5)]
     pub retry_interval: u64,
 
+    /// 连续失败时重试间隔按指数增长（retry_interval、2倍、4倍……，加±20%随机抖动）
+    #[arg(long, default_value_t = false)]
+    pub retry_backoff: bool,
+
+    /// 指数退避时重试间隔的上限（秒）
+    #[arg(long, default_value_t = 300)]
+    pub retry_max_interval: u64,
+
     /// 附件文件路径，用于发送普通文件作为附件
     #[arg(long)]
     pub attachment: Option<String>,
@@ -152,6 +163,19 @@ impl Config {
         }
     }
 
+    /// 第 `consecutive_failures` 次连续失败后（从0开始）下一轮前的等待时间
+    pub fn retry_delay(&self, consecutive_failures: u32) -> Duration {
+        if self.retry_backoff {
+            backoff::with_jitter(backoff::next_backoff(
+                consecutive_failures,
+                self.retry_interval,
+                self.retry_max_interval,
+            ))
+        } else {
+            Duration::from_secs(self.retry_interval)
+        }
+    }
+
     pub fn process_mode(&self) -> ProcessMode {
         if self.processes == "auto" {
             ProcessMode::Auto
@@ -7,6 +7,7 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 mod anonymizer;
+mod backoff;
 mod config;
 mod mailer;
 mod stats;
@@ -80,6 +81,8 @@ async fn main() -> anyhow::Result<()> {
     let mut total_stats = Stats::new();
     let total_start_time = Instant::now();
     let mut successful_iterations = 0;
+    // 连续失败的轮数，决定重试的退避等待时间
+    let mut consecutive_failures: u32 = 0;
 
     // 循环发送
     let mut current_iteration = 1;
@@ -98,38 +101,10 @@ async fn main() -> anyhow::Result<()> {
         match mailer.send_all_with_cancel(running.clone()).await {
             Ok(stats) => {
                 successful_iterations += 1;
+                consecutive_failures = 0;
 
                 // 累加统计信息
-                total_stats.email_count += stats.email_count;
-
-                // 使用迭代器的方式添加耗时数据，避免移动所有权
-                for duration in &stats.parse_durations {
-                    total_stats.parse_durations.push(*duration);
-                }
-                for duration in &stats.send_durations {
-                    total_stats.send_durations.push(*duration);
-                }
-
-                total_stats.parse_errors += stats.parse_errors;
-                total_stats.send_errors += stats.send_errors;
-
-                // 累加错误详情
-                for (error_type, count) in &stats.error_details {
-                    let entry = total_stats
-                        .error_details
-                        .entry(error_type.clone())
-                        .or_insert(0);
-                    *entry += count;
-                }
-
-                // 累加失败文件
-                for (error_type, files) in &stats.failed_files {
-                    total_stats
-                        .failed_files
-                        .entry(error_type.clone())
-                        .or_default()
-                        .extend(files.clone());
-                }
+                total_stats.merge(&stats);
 
                 info!("第 {} 轮发送完成！", current_iteration);
                 info!("{}", stats);
@@ -146,9 +121,11 @@ async fn main() -> anyhow::Result<()> {
                 if !config.r#loop || !running.load(Ordering::SeqCst) {
                     return Err(e);
                 }
-                // 否则等待后重试
-                info!("等待{}秒后重试...", config.retry_interval);
-                tokio::time::sleep(Duration::from_secs(config.retry_interval)).await;
+                // 否则等待后重试（启用退避时随连续失败次数增长）
+                let retry_delay = config.retry_delay(consecutive_failures);
+                consecutive_failures = consecutive_failures.saturating_add(1);
+                info!("等待{:.1}秒后重试...", retry_delay.as_secs_f64());
+                tokio::time::sleep(retry_delay).await;
             }
         }
 
@@ -28,6 +28,25 @@ impl Stats {
         }
     }
 
+    /// 把另一轮发送的统计累加到当前统计
+    pub fn merge(&mut self, other: &Stats) {
+        self.email_count += other.email_count;
+        self.parse_durations.extend_from_slice(&other.parse_durations);
+        self.send_durations.extend_from_slice(&other.send_durations);
+        self.total_duration += other.total_duration;
+        self.parse_errors += other.parse_errors;
+        self.send_errors += other.send_errors;
+        for (error_type, count) in &other.error_details {
+            *self.error_details.entry(error_type.clone()).or_insert(0) += count;
+        }
+        for (error_type, files) in &other.failed_files {
+            self.failed_files
+                .entry(error_type.clone())
+                .or_default()
+                .extend(files.iter().cloned());
+        }
+    }
+
     pub fn increment_error(&mut self, error_type: &str, file_path: &str) {
         *self
             .error_details